@@ -15,10 +15,12 @@ import {
 import {
   struct,
   u8,
+  u16,
 } from '@solana/buffer-layout';
 
 import {
-  publicKey
+  publicKey,
+  u64,
 } from '@solana/buffer-layout-utils';
 
 import fs from 'mz/fs';
@@ -81,6 +83,33 @@ const PROGRAM_SO_PATH = path.join(PROGRAM_PATH, 'General_Multisig.so');
  */
 const PROGRAM_KEYPAIR_PATH = path.join(PROGRAM_PATH, 'General_Multisig-keypair.json');
 
+/**
+ * Nonce distinguishing this multisig from the payer's other multisigs
+ */
+const MULTISIG_NONCE = BigInt(0);
+
+/**
+ * Role bitmask allowing an owner to propose, approve, execute and cancel
+ */
+const ROLE_ALL = 0b1111;
+
+/**
+ * Size of a transaction account: its header followed by the space reserved for instructions
+ */
+const TRANSACTION_SIZE = 132 + 1024;
+
+/**
+ * Derive the multisig address from the payer and the nonce
+ */
+async function findMultisigAddress(): Promise<[PublicKey, number]> {
+  const nonce = Buffer.alloc(8);
+  nonce.writeBigUInt64LE(MULTISIG_NONCE);
+  return PublicKey.findProgramAddress(
+    [Buffer.from('multisig', 'utf8'), payer.publicKey.toBuffer(), nonce],
+    programId,
+  );
+}
+
 
 
 /**
@@ -165,7 +194,7 @@ export async function checkProgram(): Promise<void> {
     );
 
     const lamports = await connection.getMinimumBalanceForRentExemption(
-      1024,
+      TRANSACTION_SIZE,
     );
     
     console.log(
@@ -180,7 +209,7 @@ export async function checkProgram(): Promise<void> {
         seed: Transaction_SEED,
         newAccountPubkey: TransactionPubkey,
         lamports,
-        space: 1024,
+        space: TRANSACTION_SIZE,
         programId,
       }),
     );
@@ -191,10 +220,12 @@ export async function checkProgram(): Promise<void> {
 
 export interface MultisigInstructionData {
     instruction: number;
+    nonce: bigint;
 }
 
 export const multisigInstructionData = struct<MultisigInstructionData>([
     u8('instruction'),
+    u64('nonce'),
 ]);
 
 export async function createMultisig(): Promise<void> {
@@ -203,12 +234,13 @@ export async function createMultisig(): Promise<void> {
   multisigInstructionData.encode(
       {
         instruction: 0,   
+        nonce: MULTISIG_NONCE,
       },
       data
   );
   
 
-  let [MultisigPubkey, bump] = await PublicKey.findProgramAddress([Buffer.from('You pass butter', 'utf8')], programId);
+  let [MultisigPubkey, bump] = await findMultisigAddress();
  
 
   // Check if the Config account has already been created
@@ -247,23 +279,44 @@ export async function createMultisig(): Promise<void> {
 export interface InitializeMultisigInstructionData {
     instruction: number;
     m: number;
+    nonce: bigint;
+    weight1: number;
+    roles1: number;
+    weight2: number;
+    roles2: number;
+    weight3: number;
+    roles3: number;
 }
 
 export const initializeMultisigInstructionData = struct<InitializeMultisigInstructionData>([
     u8('instruction'),
-    u8('m'),
+    u16('m'),
+    u64('nonce'),
+    u8('weight1'),
+    u8('roles1'),
+    u8('weight2'),
+    u8('roles2'),
+    u8('weight3'),
+    u8('roles3'),
 ]);
 
 /**
  *  InitializeMultisig  
  */
 export async function InitializeMultisig(): Promise<void> {
-  let [MultisigPubkey, bump] = await PublicKey.findProgramAddress([Buffer.from('You pass butter', 'utf8')], programId);
+  let [MultisigPubkey, bump] = await findMultisigAddress();
   const data = Buffer.alloc(initializeMultisigInstructionData.span);
   initializeMultisigInstructionData.encode(
       {
         instruction: 1,
         m: 2,   
+        nonce: MULTISIG_NONCE,
+        weight1: 1,
+        roles1: ROLE_ALL,
+        weight2: 1,
+        roles2: ROLE_ALL,
+        weight3: 1,
+        roles3: ROLE_ALL,
       },
       data
   );
@@ -272,6 +325,7 @@ export async function InitializeMultisig(): Promise<void> {
     keys: [
       {pubkey: MultisigPubkey, isSigner: false, isWritable: true},
       {pubkey: payer.publicKey, isSigner: true, isWritable: false},
+      {pubkey: payer.publicKey, isSigner: true, isWritable: false},
       {pubkey: signer2.publicKey, isSigner: false, isWritable: false},
       {pubkey: signer3.publicKey, isSigner: false, isWritable: false},
     ],
@@ -286,42 +340,52 @@ export async function InitializeMultisig(): Promise<void> {
   );
 }
 
-export interface CreateTransactionInstructionData {
-    instruction: number;
-    key: PublicKey;
-    _data: number;
+/**
+ * Encode a u16 little-endian length prefix
+ */
+function encodeLength(len: number): Buffer {
+  const buf = Buffer.alloc(2);
+  buf.writeUInt16LE(len);
+  return buf;
 }
 
-export const createTransactionInstructionData = struct<CreateTransactionInstructionData>([
-    u8('instruction'),
-    publicKey('key'),
-    u8('_data'),
-]);
-
 /**
  *  CreateTransaction  
  */
 export async function CreateTransaction(): Promise<void> {
-  let [MultisigPubkey, bump] = await PublicKey.findProgramAddress([Buffer.from('You pass butter', 'utf8')], programId);
+  let [MultisigPubkey, bump] = await findMultisigAddress();
   const target = new PublicKey("FSGnRdyB8UqbamPrUy1yJKuCjQN8bTiXrNxLFCPet7Y6");
   const config = new PublicKey("5q3q8FRY5jUoePoQKYjU7ZRkVC27dWTuGQ5WqPK2J9FU");
 
-  const data = Buffer.alloc(createTransactionInstructionData.span);
-  createTransactionInstructionData.encode(
-      {
-        instruction: 2,
-        key: target,
-        _data: 3,   
-      },
-      data
-  );
+  // accounts and data passed to the target program, each prefixed with its length
+  const accounts = [
+    {pubkey: config, isSigner: false, isWritable: true},
+    {pubkey: MultisigPubkey, isSigner: true, isWritable: false},
+  ];
+  const _data = Buffer.from([3]);
+
+  const data = Buffer.concat([
+    Buffer.from([2]),
+    // a single instruction
+    encodeLength(1),
+    target.toBuffer(),
+    encodeLength(accounts.length),
+    ...accounts.map(account => Buffer.concat([
+      account.pubkey.toBuffer(),
+      Buffer.from([account.isSigner ? 1 : 0, account.isWritable ? 1 : 0]),
+    ])),
+    encodeLength(_data.length),
+    _data,
+    // no timelock and no expiry
+    Buffer.alloc(9),
+    Buffer.alloc(9),
+  ]);
 
-  
   const instruction = new TransactionInstruction({
     keys: [
       {pubkey: TransactionPubkey, isSigner: false, isWritable: true},
-      {pubkey: config, isSigner: false, isWritable: true},
       {pubkey: MultisigPubkey, isSigner: false, isWritable: false},
+      {pubkey: payer.publicKey, isSigner: true, isWritable: false},
     ],
     programId,
     data: data, 
@@ -350,7 +414,7 @@ export const approve1InstructionData = struct<Approve1InstructionData>([
  *  Approve 1   
  */
 export async function Approve1(): Promise<void> {
-  let [MultisigPubkey, bump] = await PublicKey.findProgramAddress([Buffer.from('You pass butter', 'utf8')], programId);
+  let [MultisigPubkey, bump] = await findMultisigAddress();
   
   const data = Buffer.alloc(approve1InstructionData.span);
   approve1InstructionData.encode(
@@ -399,7 +463,7 @@ export const approve2InstructionData = struct<Approve2InstructionData>([
  *  Approve 2   
  */
 export async function Approve2(): Promise<void> {
-  let [MultisigPubkey, bump] = await PublicKey.findProgramAddress([Buffer.from('You pass butter', 'utf8')], programId);
+  let [MultisigPubkey, bump] = await findMultisigAddress();
   
   const data = Buffer.alloc(approve2InstructionData.span);
   approve2InstructionData.encode(
@@ -441,7 +505,7 @@ export const executeTransactionInstructionData = struct<ExecuteTransactionInstru
  *  Execute   
  */
 export async function ExecuteTransaction(): Promise<void> {
-  let [MultisigPubkey, bump] = await PublicKey.findProgramAddress([Buffer.from('You pass butter', 'utf8')], programId);
+  let [MultisigPubkey, bump] = await findMultisigAddress();
   
   const target = new PublicKey("FSGnRdyB8UqbamPrUy1yJKuCjQN8bTiXrNxLFCPet7Y6");
   const config = new PublicKey("5q3q8FRY5jUoePoQKYjU7ZRkVC27dWTuGQ5WqPK2J9FU");
@@ -456,8 +520,10 @@ export async function ExecuteTransaction(): Promise<void> {
     keys: [
       {pubkey: TransactionPubkey, isSigner: false, isWritable: true},
       {pubkey: MultisigPubkey, isSigner: false, isWritable: false},
-      {pubkey: config, isSigner: false, isWritable: true},
+      {pubkey: payer.publicKey, isSigner: true, isWritable: false},
       {pubkey: target, isSigner: false, isWritable: false},
+      {pubkey: config, isSigner: false, isWritable: true},
+      {pubkey: MultisigPubkey, isSigner: false, isWritable: false},
     ],
     programId,
     data: data, 
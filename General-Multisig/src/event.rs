@@ -0,0 +1,114 @@
+//! Events logged by the program and a decoder for them
+use borsh::{BorshDeserialize, BorshSerialize};
+#[cfg(not(target_os = "solana"))]
+use base64::{engine::general_purpose::STANDARD, Engine};
+use solana_program::{log::sol_log_data, pubkey::Pubkey};
+
+/// A multisig was initialized
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct MultisigCreated {
+    /// The multisig account
+    pub multisig: Pubkey,
+    /// The creator its address is derived from
+    pub creator: Pubkey,
+    /// The total weight of approvals required
+    pub m: u16,
+    /// The owners, in order
+    pub owners: Vec<Pubkey>,
+}
+
+/// A transaction was proposed
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct ProposalCreated {
+    /// The multisig the transaction belongs to
+    pub multisig: Pubkey,
+    /// The transaction account
+    pub transaction: Pubkey,
+    /// The owner who proposed it
+    pub proposer: Pubkey,
+}
+
+/// An owner approved a transaction
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct Approved {
+    /// The multisig the transaction belongs to
+    pub multisig: Pubkey,
+    /// The transaction account
+    pub transaction: Pubkey,
+    /// The approving owner
+    pub owner: Pubkey,
+}
+
+/// A transaction was executed
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct Executed {
+    /// The multisig the transaction belongs to
+    pub multisig: Pubkey,
+    /// The transaction account
+    pub transaction: Pubkey,
+    /// The owner who executed it
+    pub executor: Pubkey,
+}
+
+/// Events, logged with `sol_log_data` as a single borsh-encoded field whose
+/// first byte, the variant index, is the discriminator.
+/// New events must only be appended, so the discriminators stay stable.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub enum MultisigEvent {
+    /// Discriminator 0
+    MultisigCreated(MultisigCreated),
+    /// Discriminator 1
+    ProposalCreated(ProposalCreated),
+    /// Discriminator 2
+    Approved(Approved),
+    /// Discriminator 3
+    Executed(Executed),
+}
+
+impl MultisigEvent {
+    /// Logs the event as program data
+    pub fn emit(&self) {
+        sol_log_data(&[&self.try_to_vec().unwrap()]);
+    }
+
+    /// Decodes an event from the data of a `Program data:` log
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        Self::try_from_slice(data).ok()
+    }
+}
+
+/// Parses the log messages of a transaction into the events `program_id`
+/// emitted, in order. Data logged by other programs, including programs
+/// invoked by `program_id`, is ignored, as is data that is not an event.
+/// Not part of the on-chain build.
+#[cfg(not(target_os = "solana"))]
+pub fn parse_logs<S: AsRef<str>>(program_id: &Pubkey, logs: &[S]) -> Vec<MultisigEvent> {
+    let program_id = program_id.to_string();
+    // programs currently executing, innermost last
+    let mut invocations: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+    for log in logs.iter().map(AsRef::as_ref) {
+        if let Some(rest) = log.strip_prefix("Program data: ") {
+            if invocations.last() != Some(&program_id.as_str()) {
+                continue;
+            }
+            let fields = rest
+                .split(' ')
+                .map(|field| STANDARD.decode(field))
+                .collect::<Result<Vec<_>, _>>();
+            if let Ok([data]) = fields.as_deref() {
+                events.extend(MultisigEvent::decode(data));
+            }
+        } else if let Some(rest) = log.strip_prefix("Program ") {
+            let mut words = rest.split(' ');
+            match (words.next(), words.next()) {
+                (Some(program), Some("invoke")) => invocations.push(program),
+                (Some(_), Some("success")) | (Some(_), Some("failed:")) => {
+                    invocations.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    events
+}
@@ -5,8 +5,11 @@ use solana_program::{
     msg,
     program_error::ProgramError,
     pubkey::{Pubkey, PUBKEY_BYTES},
+    system_program, sysvar,
 };
-use std::mem::size_of;
+use std::{convert::TryInto, mem::size_of};
+use crate::state::{Multisig, TransactionInstruction, pack_option_i64, unpack_option_i64};
+use arrayref::array_ref;
 
 
 /// Minimum number of multisignature signers (min N)
@@ -15,27 +18,80 @@ pub const MIN_SIGNERS: usize = 1;
 pub const MAX_SIGNERS: usize = 11;
 
 /// Instructions.
-// #[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum MultisigInstruction {
     /// Allocate PDA 
-    AllocatePDA,
+    AllocatePDA {
+        /// Nonce distinguishing the multisigs of one creator
+        nonce: u64,
+    },
     /// Initialize Multisig
     InitializeMultisig {
-        /// The number of signers (M) required to validate this multisignature
-        /// account.
-        m: u8,
+        /// The total weight of approvals (M) required to validate this
+        /// multisignature account.
+        m: u16,
+        /// Nonce the multisig address was derived with
+        nonce: u64,
+        /// Voting weight of each owner, in the order the owners are passed
+        weights: Vec<u8>,
+        /// Role bitmask of each owner, in the order the owners are passed
+        roles: Vec<u8>,
     },
-    /// Create Transaction
+    /// Create Transaction. Must be signed by an owner of the multisig with
+    /// the propose role, whose approval is recorded with it if they may
+    /// approve.
+    ///
+    /// The instructions, and each instruction's accounts and data, are
+    /// prefixed with their length as a little-endian u16, the timestamps
+    /// with a presence flag.
     CreateTransaction{
-        /// the target program id to call
-        target_program_id: Pubkey,
-        /// the operation 
-        data: u8
+        /// the instructions to execute, in order
+        instructions: Vec<TransactionInstruction>,
+        /// Unix timestamp before which the transaction cannot be executed
+        valid_after: Option<i64>,
+        /// Unix timestamp from which the transaction can no longer be executed
+        expires_at: Option<i64>,
     },  
     // Approve
     Approve,
-    // Execute Transaction
+    // Execute Transaction, signed by an owner with the execute role
     ExecuteTransaction,
+    /// Replace the owners of the multisig. Must be signed by the multisig
+    /// itself, i.e. run through ExecuteTransaction.
+    SetOwners {
+        /// The new owners
+        owners: Vec<Pubkey>,
+        /// Voting weight of each new owner
+        weights: Vec<u8>,
+        /// Role bitmask of each new owner
+        roles: Vec<u8>,
+    },
+    /// Change the total weight of approvals required. Must be signed by the
+    /// multisig itself, i.e. run through ExecuteTransaction.
+    ChangeThreshold {
+        /// The total weight of approvals (M) required to validate this
+        /// multisignature account.
+        m: u16,
+    },
+    /// Reject a transaction. Once it can no longer reach the threshold it
+    /// is marked as rejected.
+    Reject,
+    /// Cancel a transaction. The proposer, or an owner with the cancel
+    /// role, may cancel it.
+    Cancel,
+    /// Close an executed, cancelled, rejected or expired transaction,
+    /// sending its lamports to the recipient chosen by the proposer.
+    CloseTransaction,
+    /// Withdraw the caller's approval of a transaction that has not been
+    /// executed yet.
+    Unapprove,
+    /// Approve a transaction on behalf of every owner with the approve role
+    /// that signed its approval digest (see `Transaction::approval_digest`)
+    /// in an ed25519 program instruction preceding this one. Anyone may
+    /// relay the signatures.
+    ///
+    /// Accounts: transaction, multisig, instructions sysvar.
+    ApproveWithSignatures,
 }
 
 impl MultisigInstruction {
@@ -44,16 +100,25 @@ impl MultisigInstruction {
         let (&tag, rest) = input.split_first().ok_or(0)?;
         Ok(match tag {
             0 => {
-                Self::AllocatePDA
+                let (nonce, _rest) = Self::unpack_u64(rest)?;
+                Self::AllocatePDA { nonce }
             }
             1 => {
-                let &m = rest.get(0).ok_or(ProgramError::InvalidArgument)?;
-                Self::InitializeMultisig { m }
+                let (m, rest) = Self::unpack_u16(rest)?;
+                let (nonce, rest) = Self::unpack_u64(rest)?;
+                if rest.len() % 2 != 0 {
+                    return Err(ProgramError::InvalidArgument);
+                }
+                let weights = rest.iter().step_by(2).copied().collect();
+                let roles = rest.iter().skip(1).step_by(2).copied().collect();
+                Self::InitializeMultisig { m, nonce, weights, roles }
             }
             2 => { 
-                let (target_program_id, _rest) = Self::unpack_pubkey(rest)?;
-                let &data = _rest.get(0).ok_or(ProgramError::InvalidArgument)?;
-                Self::CreateTransaction { target_program_id, data }
+                let (instructions, rest) = TransactionInstruction::unpack_list(rest)
+                    .map_err(|_| ProgramError::InvalidArgument)?;
+                let (valid_after, rest) = Self::unpack_option_i64(rest)?;
+                let (expires_at, _rest) = Self::unpack_option_i64(rest)?;
+                Self::CreateTransaction { instructions, valid_after, expires_at }
             }
             3 => {
                 Self::Approve
@@ -61,11 +126,106 @@ impl MultisigInstruction {
             4 => {
                 Self::ExecuteTransaction 
             }
+            5 => {
+                let (&n, mut rest) = rest.split_first().ok_or(ProgramError::InvalidArgument)?;
+                let mut owners = Vec::with_capacity(n as usize);
+                let mut weights = Vec::with_capacity(n as usize);
+                let mut roles = Vec::with_capacity(n as usize);
+                for _ in 0..n {
+                    let (owner, _rest) = Self::unpack_pubkey(rest)?;
+                    let (&weight, _rest) = _rest.split_first().ok_or(ProgramError::InvalidArgument)?;
+                    let (&role, _rest) = _rest.split_first().ok_or(ProgramError::InvalidArgument)?;
+                    owners.push(owner);
+                    weights.push(weight);
+                    roles.push(role);
+                    rest = _rest;
+                }
+                Self::SetOwners { owners, weights, roles }
+            }
+            6 => {
+                let (m, _rest) = Self::unpack_u16(rest)?;
+                Self::ChangeThreshold { m }
+            }
+            7 => {
+                Self::Reject
+            }
+            8 => {
+                Self::Cancel
+            }
+            9 => {
+                Self::CloseTransaction
+            }
+            10 => {
+                Self::Unapprove
+            }
+            11 => {
+                Self::ApproveWithSignatures
+            }
             _ => {
                 return Err(ProgramError::InvalidArgument)
             }
         })
     }
+
+    /// Packs a [MultisigInstruction](enum.MultisigInstruction.html) into a byte buffer.
+    ///
+    /// Panics if the owners, weights and roles of an instruction differ in length.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(size_of::<Self>());
+        match self {
+            Self::AllocatePDA { nonce } => {
+                buf.push(0);
+                buf.extend_from_slice(&nonce.to_le_bytes());
+            }
+            Self::InitializeMultisig { m, nonce, weights, roles } => {
+                buf.push(1);
+                buf.extend_from_slice(&m.to_le_bytes());
+                buf.extend_from_slice(&nonce.to_le_bytes());
+                assert_eq!(weights.len(), roles.len(), "one role per weight");
+                for (weight, role) in weights.iter().zip(roles.iter()) {
+                    buf.push(*weight);
+                    buf.push(*role);
+                }
+            }
+            Self::CreateTransaction { instructions, valid_after, expires_at } => {
+                buf.push(2);
+                let len = 2 + instructions.iter().map(|instruction| instruction.packed_len()).sum::<usize>();
+                let start = buf.len();
+                buf.resize(start + len, 0);
+                TransactionInstruction::pack_list(instructions, &mut buf[start..]);
+                let mut option = [0; 9];
+                pack_option_i64(*valid_after, &mut option);
+                buf.extend_from_slice(&option);
+                pack_option_i64(*expires_at, &mut option);
+                buf.extend_from_slice(&option);
+            }
+            Self::Approve => buf.push(3),
+            Self::ExecuteTransaction => buf.push(4),
+            Self::SetOwners { owners, weights, roles } => {
+                buf.push(5);
+                assert!(
+                    owners.len() == weights.len() && owners.len() == roles.len(),
+                    "one weight and role per owner"
+                );
+                buf.push(owners.len() as u8);
+                for ((owner, weight), role) in owners.iter().zip(weights.iter()).zip(roles.iter()) {
+                    buf.extend_from_slice(owner.as_ref());
+                    buf.push(*weight);
+                    buf.push(*role);
+                }
+            }
+            Self::ChangeThreshold { m } => {
+                buf.push(6);
+                buf.extend_from_slice(&m.to_le_bytes());
+            }
+            Self::Reject => buf.push(7),
+            Self::Cancel => buf.push(8),
+            Self::CloseTransaction => buf.push(9),
+            Self::Unapprove => buf.push(10),
+            Self::ApproveWithSignatures => buf.push(11),
+        }
+        buf
+    }
     /// unpack pubkey
     pub fn unpack_pubkey(input: &[u8]) -> Result<(Pubkey, &[u8]), ProgramError> {
         if input.len() < PUBKEY_BYTES {
@@ -76,10 +236,324 @@ impl MultisigInstruction {
         let pk = Pubkey::new(key);
         Ok((pk, rest))
     }
+    /// unpack u16
+    pub fn unpack_u16(input: &[u8]) -> Result<(u16, &[u8]), ProgramError> {
+        if input.len() < 2 {
+            msg!("u16 cannot be unpacked");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let (value, rest) = input.split_at(2);
+        Ok((u16::from_le_bytes([value[0], value[1]]), rest))
+    }
+    /// unpack u64
+    pub fn unpack_u64(input: &[u8]) -> Result<(u64, &[u8]), ProgramError> {
+        if input.len() < 8 {
+            msg!("u64 cannot be unpacked");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let (value, rest) = input.split_at(8);
+        let value = value
+            .try_into()
+            .map(u64::from_le_bytes)
+            .map_err(|_| ProgramError::InvalidArgument)?;
+        Ok((value, rest))
+    }
+    /// unpack optional i64
+    pub fn unpack_option_i64(input: &[u8]) -> Result<(Option<i64>, &[u8]), ProgramError> {
+        if input.len() < 9 {
+            msg!("Option<i64> cannot be unpacked");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let (value, rest) = input.split_at(9);
+        let value = unpack_option_i64(array_ref![value, 0, 9])
+            .map_err(|_| ProgramError::InvalidArgument)?;
+        Ok((value, rest))
+    }
     
 
 }
 
+/// Creates an `AllocatePDA` instruction, creating the multisig account of
+/// `creator` for `nonce`.
+pub fn allocate_pda(
+    program_id: &Pubkey,
+    creator: &Pubkey,
+    nonce: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = MultisigInstruction::AllocatePDA { nonce }.pack();
+    let (multisig, _) = Multisig::find_address(program_id, creator, nonce);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(multisig, false),
+        AccountMeta::new(*creator, true),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `InitializeMultisig` instruction. `signers`, `weights` and
+/// `roles` describe the owners, in order.
+pub fn initialize_multisig(
+    program_id: &Pubkey,
+    multisig: &Pubkey,
+    creator: &Pubkey,
+    signers: &[&Pubkey],
+    m: u16,
+    nonce: u64,
+    weights: &[u8],
+    roles: &[u8],
+) -> Result<Instruction, ProgramError> {
+    if !is_valid_signer_index(signers.len())
+        || signers.len() != weights.len()
+        || signers.len() != roles.len()
+    {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let data = MultisigInstruction::InitializeMultisig {
+        m,
+        nonce,
+        weights: weights.to_vec(),
+        roles: roles.to_vec(),
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(2 + signers.len());
+    accounts.push(AccountMeta::new(*multisig, false));
+    accounts.push(AccountMeta::new_readonly(*creator, true));
+    for signer_pubkey in signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CreateTransaction` instruction
+pub fn create_transaction(
+    program_id: &Pubkey,
+    transaction: &Pubkey,
+    multisig: &Pubkey,
+    proposer: &Pubkey,
+    instructions: Vec<TransactionInstruction>,
+    valid_after: Option<i64>,
+    expires_at: Option<i64>,
+) -> Result<Instruction, ProgramError> {
+    let data = MultisigInstruction::CreateTransaction {
+        instructions,
+        valid_after,
+        expires_at,
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*transaction, false),
+        AccountMeta::new_readonly(*multisig, false),
+        AccountMeta::new_readonly(*proposer, true),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an instruction signed by an owner acting on a transaction
+fn owner_instruction(
+    program_id: &Pubkey,
+    instruction: MultisigInstruction,
+    transaction: &Pubkey,
+    multisig: &Pubkey,
+    owner: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let accounts = vec![
+        AccountMeta::new(*transaction, false),
+        AccountMeta::new_readonly(*multisig, false),
+        AccountMeta::new_readonly(*owner, true),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.pack(),
+    })
+}
+
+/// Creates an `Approve` instruction
+pub fn approve(
+    program_id: &Pubkey,
+    transaction: &Pubkey,
+    multisig: &Pubkey,
+    owner: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    owner_instruction(program_id, MultisigInstruction::Approve, transaction, multisig, owner)
+}
+
+/// Creates an `Unapprove` instruction
+pub fn unapprove(
+    program_id: &Pubkey,
+    transaction: &Pubkey,
+    multisig: &Pubkey,
+    owner: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    owner_instruction(program_id, MultisigInstruction::Unapprove, transaction, multisig, owner)
+}
+
+/// Creates a `Reject` instruction
+pub fn reject(
+    program_id: &Pubkey,
+    transaction: &Pubkey,
+    multisig: &Pubkey,
+    owner: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    owner_instruction(program_id, MultisigInstruction::Reject, transaction, multisig, owner)
+}
+
+/// Creates a `Cancel` instruction
+pub fn cancel(
+    program_id: &Pubkey,
+    transaction: &Pubkey,
+    multisig: &Pubkey,
+    canceller: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    owner_instruction(program_id, MultisigInstruction::Cancel, transaction, multisig, canceller)
+}
+
+/// Creates an `ApproveWithSignatures` instruction. It must follow the
+/// ed25519 program instructions carrying the owners' signatures.
+pub fn approve_with_signatures(
+    program_id: &Pubkey,
+    transaction: &Pubkey,
+    multisig: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = MultisigInstruction::ApproveWithSignatures.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*transaction, false),
+        AccountMeta::new_readonly(*multisig, false),
+        AccountMeta::new_readonly(sysvar::instructions::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `ExecuteTransaction` instruction. `instructions` are the ones
+/// stored in the transaction; their programs and accounts are passed in
+/// order. Recorded signers other than the multisig and its vault, which the
+/// program signs for, must sign the outer transaction.
+pub fn execute_transaction(
+    program_id: &Pubkey,
+    transaction: &Pubkey,
+    multisig: &Pubkey,
+    executor: &Pubkey,
+    instructions: &[TransactionInstruction],
+) -> Result<Instruction, ProgramError> {
+    let data = MultisigInstruction::ExecuteTransaction.pack();
+    let (vault, _) = Multisig::find_vault_address(program_id, multisig);
+
+    let mut accounts = vec![
+        AccountMeta::new(*transaction, false),
+        AccountMeta::new(*multisig, false),
+        AccountMeta::new_readonly(*executor, true),
+    ];
+    for instruction in instructions.iter() {
+        accounts.push(AccountMeta::new_readonly(instruction.program_id, false));
+        for account in instruction.accounts.iter() {
+            let is_signer = account.is_signer && account.pubkey != *multisig && account.pubkey != vault;
+            accounts.push(AccountMeta {
+                pubkey: account.pubkey,
+                is_signer,
+                is_writable: account.is_writable,
+            });
+        }
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CloseTransaction` instruction
+pub fn close_transaction(
+    program_id: &Pubkey,
+    transaction: &Pubkey,
+    proposer: &Pubkey,
+    recipient: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = MultisigInstruction::CloseTransaction.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*transaction, false),
+        AccountMeta::new_readonly(*proposer, true),
+        AccountMeta::new(*recipient, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetOwners` instruction, to be proposed through
+/// `create_transaction` as it must be signed by the multisig.
+pub fn set_owners(
+    program_id: &Pubkey,
+    multisig: &Pubkey,
+    owners: &[&Pubkey],
+    weights: &[u8],
+    roles: &[u8],
+) -> Result<Instruction, ProgramError> {
+    if !is_valid_signer_index(owners.len())
+        || owners.len() != weights.len()
+        || owners.len() != roles.len()
+    {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let data = MultisigInstruction::SetOwners {
+        owners: owners.iter().map(|owner| **owner).collect(),
+        weights: weights.to_vec(),
+        roles: roles.to_vec(),
+    }
+    .pack();
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts: vec![AccountMeta::new(*multisig, true)],
+        data,
+    })
+}
+
+/// Creates a `ChangeThreshold` instruction, to be proposed through
+/// `create_transaction` as it must be signed by the multisig.
+pub fn change_threshold(
+    program_id: &Pubkey,
+    multisig: &Pubkey,
+    m: u16,
+) -> Result<Instruction, ProgramError> {
+    let data = MultisigInstruction::ChangeThreshold { m }.pack();
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts: vec![AccountMeta::new(*multisig, true)],
+        data,
+    })
+}
+
 /// Utility function that checks index is between MIN_SIGNERS and MAX_SIGNERS
 pub fn is_valid_signer_index(index: usize) -> bool {
     (MIN_SIGNERS..=MAX_SIGNERS).contains(&index)
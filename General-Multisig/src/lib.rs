@@ -8,3 +8,5 @@ pub mod processor;
 pub mod instruction;
 /// state
 pub mod state;
+/// events
+pub mod event;
@@ -1,5 +1,6 @@
 //! Program instruction processor
-use crate::{state::{Multisig, Transaction, TransactionAccount}, instruction::{MultisigInstruction, is_valid_signer_index, MAX_SIGNERS}};
+use crate::{state::{Multisig, Transaction, TransactionInstruction, TransactionStatus, MULTISIG_SEED, VAULT_SEED, ROLE_ALL, ROLE_APPROVE, ROLE_CANCEL, ROLE_EXECUTE, ROLE_PROPOSE}, instruction::{MultisigInstruction, is_valid_signer_index, MAX_SIGNERS}};
+use crate::event::{Approved, Executed, MultisigCreated, MultisigEvent, ProposalCreated};
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
@@ -9,15 +10,16 @@ use solana_program::{
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack, Sealed},
     instruction::{AccountMeta, Instruction},
-    program::invoke_signed,
-    system_instruction
+    program::{invoke, invoke_signed},
+    system_instruction,
+    system_program,
+    sysvar::{clock::Clock, rent::Rent, instructions::{load_current_index_checked, load_instruction_at_checked}, Sysvar},
+    ed25519_program,
 };
 use std::{convert::TryInto, vec::Vec};
+use arrayref::array_ref;
 
 
-/// Size of PDA 
-pub const SIZE: usize = 1024;
-
 /// Instruction processor
 pub fn process_instruction(
     program_id: &Pubkey,
@@ -27,22 +29,28 @@ pub fn process_instruction(
     let instruction = MultisigInstruction::unpack(instruction_data)?;
 
     match instruction {
-        MultisigInstruction::AllocatePDA => {
+        MultisigInstruction::AllocatePDA {
+             nonce
+        } => {
             msg!("Instruction: Allocate");
-            AllocatePDA(program_id, accounts)
+            AllocatePDA(program_id, accounts, nonce)
         }
         MultisigInstruction::InitializeMultisig {
-             m
+             m,
+             nonce,
+             weights,
+             roles
         } => {
             msg!("Instruction: InitializeMultisig");
-            InitializeMultisig(program_id, accounts, m)
+            InitializeMultisig(program_id, accounts, m, nonce, weights, roles)
         }
         MultisigInstruction::CreateTransaction {
-            target_program_id,
-            data
+            instructions,
+            valid_after,
+            expires_at,
         }=> {
             msg!("Instruction: CreateTransaction");
-            CreateTransaction(program_id, accounts, target_program_id, data)
+            CreateTransaction(program_id, accounts, instructions, valid_after, expires_at)
         }
         MultisigInstruction::Approve => {
             msg!("Instruction: Approve");
@@ -52,6 +60,40 @@ pub fn process_instruction(
             msg!("Instruction: ExecuteTransaction");
             ExecuteTransaction(program_id, accounts)
         }        
+        MultisigInstruction::SetOwners {
+            owners,
+            weights,
+            roles
+        } => {
+            msg!("Instruction: SetOwners");
+            SetOwners(program_id, accounts, owners, weights, roles)
+        }
+        MultisigInstruction::ChangeThreshold {
+            m
+        } => {
+            msg!("Instruction: ChangeThreshold");
+            ChangeThreshold(program_id, accounts, m)
+        }
+        MultisigInstruction::Reject => {
+            msg!("Instruction: Reject");
+            Reject(program_id, accounts)
+        }
+        MultisigInstruction::Cancel => {
+            msg!("Instruction: Cancel");
+            Cancel(program_id, accounts)
+        }
+        MultisigInstruction::CloseTransaction => {
+            msg!("Instruction: CloseTransaction");
+            CloseTransaction(program_id, accounts)
+        }
+        MultisigInstruction::Unapprove => {
+            msg!("Instruction: Unapprove");
+            Unapprove(program_id, accounts)
+        }
+        MultisigInstruction::ApproveWithSignatures => {
+            msg!("Instruction: ApproveWithSignatures");
+            ApproveWithSignatures(program_id, accounts)
+        }
     }
 }
 
@@ -60,6 +102,7 @@ pub fn process_instruction(
 pub fn AllocatePDA(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
+    nonce: u64,
 ) -> ProgramResult {
     // Create in iterator to safety reference accounts in the slice
     let account_info_iter = &mut accounts.iter();
@@ -69,50 +112,101 @@ pub fn AllocatePDA(
     // Account info to allocate
     let allocated_info = next_account_info(account_info_iter)?;
 
+    // Account info of the creator, which pays for the account and seeds its address
     let owner_info = next_account_info(account_info_iter)?;
 
     let (expected_allocated_key, bump) =
-        Pubkey::find_program_address(&[b"You pass butter"], program_id);
+        Multisig::find_address(program_id, owner_info.key, nonce);
     
     if *allocated_info.key != expected_allocated_key {
         // allocated key does not match the derived address
         return Err(ProgramError::InvalidArgument);
     }
 
-    // Invoke the system program to create account 
-    invoke_signed(
-        &system_instruction::create_account(
-            &owner_info.key,
-            &allocated_info.key,
-            /// lamports
-            80179200,
-            SIZE as u64,
-            &program_id, 
-            ),
-        // Order doesn't matter and this slice could include all the accounts and be:
-        // `&accounts`
-        &[  owner_info.clone(),
-            system_program_info.clone(), // program being invoked also needs to be included
-            allocated_info.clone(),
-        ],
-
-        &[&[b"You pass butter", &[bump]]],
+    create_pda_account(
+        owner_info,
+        allocated_info,
+        system_program_info,
+        Multisig::LEN,
+        program_id,
+        &[MULTISIG_SEED, owner_info.key.as_ref(), &nonce.to_le_bytes(), &[bump]],
     )?;
 
     Ok(())
 }
 
+/// Creates the PDA `allocated_info` with `space` bytes owned by `owner`,
+/// funded by `payer_info` with exactly the rent-exempt minimum. If the
+/// address already holds lamports, which would make `create_account` fail,
+/// it is topped up, then allocated and assigned instead.
+pub fn create_pda_account<'a>(
+    payer_info: &AccountInfo<'a>,
+    allocated_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    space: usize,
+    owner: &Pubkey,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    let required_lamports = Rent::get()?.minimum_balance(space);
+    let current_lamports = allocated_info.lamports();
+    let account_infos = &[
+        payer_info.clone(),
+        system_program_info.clone(),
+        allocated_info.clone(),
+    ];
+
+    if current_lamports == 0 {
+        return invoke_signed(
+            &system_instruction::create_account(
+                payer_info.key,
+                allocated_info.key,
+                required_lamports,
+                space as u64,
+                owner,
+            ),
+            account_infos,
+            &[signer_seeds],
+        );
+    }
+
+    let top_up = required_lamports.saturating_sub(current_lamports);
+    if top_up > 0 {
+        invoke(
+            &system_instruction::transfer(payer_info.key, allocated_info.key, top_up),
+            account_infos,
+        )?;
+    }
+    invoke_signed(
+        &system_instruction::allocate(allocated_info.key, space as u64),
+        account_infos,
+        &[signer_seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(allocated_info.key, owner),
+        account_infos,
+        &[signer_seeds],
+    )
+}
+
 /// Initialize Multisig
 pub fn InitializeMultisig(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    m: u8,
+    m: u16,
+    nonce: u64,
+    weights: Vec<u8>,
+    roles: Vec<u8>,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let multisig_info = next_account_info(account_info_iter)?;
+    let creator_info = next_account_info(account_info_iter)?;
      
+    if !creator_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
     let (expected_allocated_key, bump) =
-        Pubkey::find_program_address(&[b"You pass butter"], program_id);
+        Multisig::find_address(program_id, creator_info.key, nonce);
     
     if *multisig_info.key != expected_allocated_key {
         return Err(ProgramError::InvalidArgument);
@@ -125,21 +219,34 @@ pub fn InitializeMultisig(
     }
      
     let signer_infos = account_info_iter.as_slice();
+    let owners: Vec<Pubkey> = signer_infos.iter().map(|signer_info| *signer_info.key).collect();
+    validate_owners(&owners)?;
     multisig.m = m;
     multisig.n = signer_infos.len() as u8;
-    if !is_valid_signer_index(multisig.n as usize) {
-        return Err(ProgramError::InvalidArgument);
-    }
-    if !is_valid_signer_index(multisig.m as usize) {
-        return Err(ProgramError::InvalidArgument);
-    }
+    validate_weights_and_roles(signer_infos.len(), &weights, &roles)?;
     for (i, signer_info) in signer_infos.iter().enumerate() {
         multisig.signers[i] = *signer_info.key;
+        multisig.weights[i] = weights[i];
+        multisig.roles[i] = roles[i];
     }
+    if multisig.m == 0 || multisig.m as u32 > multisig.total_weight() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    multisig.creator = *creator_info.key;
+    multisig.nonce = nonce;
+    multisig.bump = bump;
+    multisig.vault_bump = Multisig::find_vault_address(program_id, multisig_info.key).1;
     multisig.is_initialized = true;
+    let event = MultisigEvent::MultisigCreated(MultisigCreated {
+        multisig: *multisig_info.key,
+        creator: multisig.creator,
+        m: multisig.m,
+        owners: multisig.signers[0..multisig.n as usize].to_vec(),
+    });
      
     /// serializing
     Multisig::pack(multisig, &mut multisig_info.data.borrow_mut())?;
+    event.emit();
 
     Ok(())
 }
@@ -148,44 +255,75 @@ pub fn InitializeMultisig(
 pub fn CreateTransaction(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    target_program_id: Pubkey,
-    data: u8,
+    instructions: Vec<TransactionInstruction>,
+    valid_after: Option<i64>,
+    expires_at: Option<i64>,
 ) -> ProgramResult {
      let account_info_iter = &mut accounts.iter();
      let transaction_info = next_account_info(account_info_iter)?;
-     let account1_info = next_account_info(account_info_iter)?;
-     let account2_info = next_account_info(account_info_iter)?;
+     let multisig_info = next_account_info(account_info_iter)?;
+     let proposer_info = next_account_info(account_info_iter)?;
+
+     check_account_owner(program_id, transaction_info)?;
+     check_account_owner(program_id, multisig_info)?;
+     if !proposer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+     }
       
      /// deserializing 
+     let multisig = Multisig::unpack(&multisig_info.data.borrow())?;
+     // only owners may propose, and proposing counts as their approval
+     let position = multisig.position(proposer_info.key).ok_or(ProgramError::InvalidArgument)?;
+     if !multisig.has_role(position, ROLE_PROPOSE) {
+        return Err(ProgramError::InvalidArgument);
+     }
+
      let mut transaction = Transaction::unpack_unchecked(&transaction_info.data.borrow())?;
      if transaction.is_initialized {
         return Err(ProgramError::InvalidArgument);
      }
      
-     let mut account1 = TransactionAccount {
-             pubkey: *account1_info.key,
-             is_signer: account1_info.is_signer,
-             is_writable: account1_info.is_writable
-     };
-     let mut account2 = TransactionAccount {
-             pubkey: *account2_info.key,
-             is_signer: account2_info.is_signer,
-             is_writable: account2_info.is_writable
-     };
-     
-     let (expected_allocated_key, bump) =
-        Pubkey::find_program_address(&[b"You pass butter"], program_id);
-     
-     transaction.multisig = expected_allocated_key;
-     transaction.program_id = target_program_id;
-     transaction.accounts = [account1, account2];
-     transaction.data = data;
+     transaction.multisig = *multisig_info.key;
+     transaction.proposer = *proposer_info.key;
+     transaction.instructions = instructions;
      transaction.signers = [false; MAX_SIGNERS];
-     transaction.did_execute = false;
+     transaction.signers[position] = multisig.has_role(position, ROLE_APPROVE);
+     transaction.rejections = [false; MAX_SIGNERS];
+     transaction.vote_counts = [0; MAX_SIGNERS];
+     transaction.status = TransactionStatus::Active;
+     transaction.valid_after = valid_after;
+     transaction.expires_at = expires_at;
+     transaction.owner_set_seqno = multisig.owner_set_seqno;
      transaction.is_initialized = true;
 
+     if let Some(expires_at) = expires_at {
+        if expires_at <= Clock::get()?.unix_timestamp
+            || valid_after.map_or(false, |valid_after| valid_after >= expires_at)
+        {
+            return Err(ProgramError::InvalidArgument);
+        }
+     }
+
+     if transaction.packed_len() > Transaction::LEN {
+        return Err(ProgramError::InvalidInstructionData);
+     }
+
      /// serializing
      Transaction::pack(transaction, &mut transaction_info.data.borrow_mut())?;
+     MultisigEvent::ProposalCreated(ProposalCreated {
+        multisig: *multisig_info.key,
+        transaction: *transaction_info.key,
+        proposer: *proposer_info.key,
+     })
+     .emit();
+     if multisig.has_role(position, ROLE_APPROVE) {
+        MultisigEvent::Approved(Approved {
+            multisig: *multisig_info.key,
+            transaction: *transaction_info.key,
+            owner: *proposer_info.key,
+        })
+        .emit();
+     }
 
      Ok(())
 }
@@ -204,15 +342,28 @@ pub fn Approve(
     check_account_owner(program_id, transaction_info)?;
     check_account_owner(program_id, multisig_info)?;
     /// deserializing
-    let mut transaction = Transaction::unpack_unchecked(&transaction_info.data.borrow())?;
-    let mut multisig = Multisig::unpack_unchecked(&multisig_info.data.borrow())?;
+    let mut transaction = Transaction::unpack(&transaction_info.data.borrow())?;
+    let multisig = Multisig::unpack(&multisig_info.data.borrow())?;
+    check_transaction_multisig(&transaction, multisig_info)?;
+    check_transaction_active(&transaction)?;
+    check_transaction_seqno(&transaction, &multisig)?;
     
     for (position, key) in multisig.signers[0..multisig.n as usize].iter().enumerate() {
         if cmp_pubkeys(key, your_info.key) && !transaction.signers[position] {
             if !your_info.is_signer {
                 return Err(ProgramError::MissingRequiredSignature);
             }
+            if !multisig.has_role(position, ROLE_APPROVE) {
+                return Err(ProgramError::InvalidArgument);
+            }
             transaction.signers[position] = true;
+            transaction.rejections[position] = false;
+            MultisigEvent::Approved(Approved {
+                multisig: *multisig_info.key,
+                transaction: *transaction_info.key,
+                owner: *your_info.key,
+            })
+            .emit();
         }     
     }
     Transaction::pack(transaction, &mut transaction_info.data.borrow_mut())?;
@@ -220,6 +371,139 @@ pub fn Approve(
     Ok(())
 }
 
+/// Approve with ed25519 signatures verified by preceding instructions
+pub fn ApproveWithSignatures(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let transaction_info = next_account_info(account_info_iter)?;
+    let multisig_info = next_account_info(account_info_iter)?;
+    let instructions_info = next_account_info(account_info_iter)?;
+
+    check_account_owner(program_id, transaction_info)?;
+    check_account_owner(program_id, multisig_info)?;
+    // deserializing
+    let mut transaction = Transaction::unpack(&transaction_info.data.borrow())?;
+    let multisig = Multisig::unpack(&multisig_info.data.borrow())?;
+    check_transaction_multisig(&transaction, multisig_info)?;
+    check_transaction_active(&transaction)?;
+    check_transaction_seqno(&transaction, &multisig)?;
+
+    // load_current_index_checked also checks this is the instructions sysvar
+    let current_index = load_current_index_checked(instructions_info)?;
+    let mut approved = false;
+    for index in 0..current_index {
+        let instruction = load_instruction_at_checked(index as usize, instructions_info)?;
+        if !cmp_pubkeys(&instruction.program_id, &ed25519_program::id()) {
+            continue;
+        }
+        for (signer, message) in unpack_ed25519_signatures(&instruction.data)? {
+            let position = multisig.position(&signer);
+            let vote_count = position.map_or(0, |position| transaction.vote_counts[position]);
+            let digest = transaction.approval_digest(program_id, transaction_info.key, &signer, vote_count);
+            if message != digest.as_ref() {
+                continue;
+            }
+            let position = position.ok_or(ProgramError::InvalidArgument)?;
+            if !multisig.has_role(position, ROLE_APPROVE) {
+                return Err(ProgramError::InvalidArgument);
+            }
+            transaction.signers[position] = true;
+            transaction.rejections[position] = false;
+            approved = true;
+            MultisigEvent::Approved(Approved {
+                multisig: *multisig_info.key,
+                transaction: *transaction_info.key,
+                owner: signer,
+            })
+            .emit();
+        }
+    }
+    if !approved {
+        msg!("No signature over the approval digest");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // serializing
+    Transaction::pack(transaction, &mut transaction_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+/// Returns the public key and message of every signature an ed25519 program
+/// instruction verified. Only signatures whose key and message are embedded
+/// in the instruction itself are returned, as the ed25519 program does not
+/// tie data read from other instructions to this one.
+pub fn unpack_ed25519_signatures(data: &[u8]) -> Result<Vec<(Pubkey, &[u8])>, ProgramError> {
+    const OFFSETS_START: usize = 2;
+    const OFFSETS_LEN: usize = 14;
+    const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+    let read_u16 = |offset: usize| -> Result<u16, ProgramError> {
+        data.get(offset..offset + 2)
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+            .ok_or(ProgramError::InvalidInstructionData)
+    };
+    let num_signatures = *data.first().ok_or(ProgramError::InvalidInstructionData)? as usize;
+    let mut signatures = Vec::with_capacity(num_signatures);
+    for i in 0..num_signatures {
+        let start = OFFSETS_START + i * OFFSETS_LEN;
+        let public_key_offset = read_u16(start + 4)? as usize;
+        let public_key_instruction_index = read_u16(start + 6)?;
+        let message_data_offset = read_u16(start + 8)? as usize;
+        let message_data_size = read_u16(start + 10)? as usize;
+        let message_instruction_index = read_u16(start + 12)?;
+        if public_key_instruction_index != CURRENT_INSTRUCTION
+            || message_instruction_index != CURRENT_INSTRUCTION
+        {
+            continue;
+        }
+        let public_key = data
+            .get(public_key_offset..public_key_offset + PUBKEY_BYTES)
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        let message = data
+            .get(message_data_offset..message_data_offset + message_data_size)
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        signatures.push((Pubkey::new_from_array(*array_ref![public_key, 0, PUBKEY_BYTES]), message));
+    }
+    Ok(signatures)
+}
+
+/// Unapprove
+pub fn Unapprove(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let transaction_info = next_account_info(account_info_iter)?;
+    let multisig_info = next_account_info(account_info_iter)?;
+    let your_info = next_account_info(account_info_iter)?;
+
+    check_account_owner(program_id, transaction_info)?;
+    check_account_owner(program_id, multisig_info)?;
+    if !your_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    // deserializing
+    let mut transaction = Transaction::unpack(&transaction_info.data.borrow())?;
+    let multisig = Multisig::unpack(&multisig_info.data.borrow())?;
+    check_transaction_multisig(&transaction, multisig_info)?;
+    check_transaction_active(&transaction)?;
+
+    let position = multisig.position(your_info.key).ok_or(ProgramError::InvalidArgument)?;
+    if !transaction.signers[position] {
+        return Err(ProgramError::InvalidArgument);
+    }
+    transaction.signers[position] = false;
+    transaction.vote_counts[position] = transaction.vote_counts[position].wrapping_add(1);
+
+    // serializing
+    Transaction::pack(transaction, &mut transaction_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
 /// Execute Transaction
 pub fn ExecuteTransaction(
     program_id: &Pubkey,
@@ -228,63 +512,383 @@ pub fn ExecuteTransaction(
     let account_info_iter = &mut accounts.iter();
     let transaction_info = next_account_info(account_info_iter)?;
     let multisig_info = next_account_info(account_info_iter)?;
-    
-    let config_info = next_account_info(account_info_iter)?;
+    let executor_info = next_account_info(account_info_iter)?;
+    // followed, for each instruction, by its target program and then the
+    // accounts passed to it, in the order they were recorded
 
-    let target_info = next_account_info(account_info_iter)?;
     check_account_owner(program_id, transaction_info)?;
     check_account_owner(program_id, multisig_info)?;
 
     /// deserializing
-    let mut transaction = Transaction::unpack_unchecked(&transaction_info.data.borrow())?;
-    let mut multisig = Multisig::unpack_unchecked(&multisig_info.data.borrow())?;
+    let mut transaction = Transaction::unpack(&transaction_info.data.borrow())?;
+    let multisig = Multisig::unpack(&multisig_info.data.borrow())?;
+    check_transaction_multisig(&transaction, multisig_info)?;
+    check_transaction_active(&transaction)?;
+    check_transaction_seqno(&transaction, &multisig)?;
+    check_transaction_window(&transaction, Clock::get()?.unix_timestamp)?;
+
+    let position = multisig.position(executor_info.key).ok_or(ProgramError::InvalidArgument)?;
+    if !executor_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !multisig.has_role(position, ROLE_EXECUTE) {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    /// weight of valid signatures
+    if multisig.weight_of(&transaction.signers) < multisig.m as u32 {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
     
+    let vault_key = Pubkey::create_program_address(
+        &[VAULT_SEED, multisig_info.key.as_ref(), &[multisig.vault_bump]],
+        program_id,
+    )?;
 
+    let mut invocations = Vec::with_capacity(transaction.instructions.len());
+    for instruction in transaction.instructions.iter() {
+        let target_info = next_account_info(account_info_iter)?;
+        if !cmp_pubkeys(target_info.key, &instruction.program_id) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
 
-    /// number of valid signatures
-    let mut num_signers = 0;
-    for (position, _) in transaction.signers[0..multisig.n as usize].iter().enumerate() {
-        if  transaction.signers[position] {
-            num_signers += 1
-        }     
+        let mut metas = Vec::with_capacity(instruction.accounts.len());
+        let mut account_infos = Vec::with_capacity(instruction.accounts.len() + 1);
+        for account in instruction.accounts.iter() {
+            let account_info = next_account_info(account_info_iter)?;
+            if !cmp_pubkeys(&account.pubkey, account_info.key) {
+                return Err(ProgramError::InvalidArgument);
+            }
+            // only the multisig and vault PDAs are signed for by this program
+            let is_signer = account.is_signer
+                || cmp_pubkeys(&account.pubkey, multisig_info.key)
+                || cmp_pubkeys(&account.pubkey, &vault_key);
+            metas.push(AccountMeta {
+                pubkey: account.pubkey,
+                is_signer,
+                is_writable: account.is_writable,
+            });
+            account_infos.push(account_info.clone());
+        }
+        account_infos.push(target_info.clone());
+
+        let ix = Instruction {
+            program_id: instruction.program_id, 
+            accounts: metas,
+            data: instruction.data.clone(),
+        };
+        invocations.push((ix, account_infos));
+    }
+    if account_info_iter.next().is_some() {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // mark the transaction executed before invoking, so it can never run twice
+    transaction.status = TransactionStatus::Executed;
+    Transaction::pack(transaction, &mut transaction_info.data.borrow_mut())?;
+
+    let nonce = multisig.nonce.to_le_bytes();
+    let signer_seeds: &[&[u8]] = &[
+        MULTISIG_SEED,
+        multisig.creator.as_ref(),
+        &nonce,
+        &[multisig.bump],
+    ];
+    let vault_signer_seeds: &[&[u8]] = &[
+        VAULT_SEED,
+        multisig_info.key.as_ref(),
+        &[multisig.vault_bump],
+    ];
+    // a failing instruction aborts the whole transaction, so either all run or none
+    for (ix, account_infos) in invocations.iter() {
+        invoke_signed(ix, account_infos, &[signer_seeds, vault_signer_seeds])?;
     }
+    MultisigEvent::Executed(Executed {
+        multisig: *multisig_info.key,
+        transaction: *transaction_info.key,
+        executor: *executor_info.key,
+    })
+    .emit();
     
-    if num_signers < multisig.m {
+    Ok(())
+
+}
+
+
+
+/// Reject
+pub fn Reject(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let transaction_info = next_account_info(account_info_iter)?;
+    let multisig_info = next_account_info(account_info_iter)?;
+    let your_info = next_account_info(account_info_iter)?;
+
+    check_account_owner(program_id, transaction_info)?;
+    check_account_owner(program_id, multisig_info)?;
+    if !your_info.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
-    
-    let mut vec1 = Vec::new(); 
-    let mut meta1 = AccountMeta::new(transaction.accounts[0].pubkey, transaction.accounts[0].is_signer);
-    let mut meta2 = AccountMeta::new_readonly(transaction.accounts[1].pubkey, true);
-
-    vec1.push(meta1);
-    vec1.push(meta2);
-    let mut vec2 = Vec::new();
-    vec2.push(transaction.data);
-    let mut ix = Instruction {
-        program_id: transaction.program_id, 
-        accounts: vec1,
-        data: vec2,
-    };
-    
-    let (expected_allocated_key, bump) =
-        Pubkey::find_program_address(&[b"You pass butter"], program_id);
+    // deserializing
+    let mut transaction = Transaction::unpack(&transaction_info.data.borrow())?;
+    let multisig = Multisig::unpack(&multisig_info.data.borrow())?;
+    check_transaction_multisig(&transaction, multisig_info)?;
+    check_transaction_active(&transaction)?;
+    check_transaction_seqno(&transaction, &multisig)?;
+
+    let position = multisig.position(your_info.key).ok_or(ProgramError::InvalidArgument)?;
+    if !multisig.has_role(position, ROLE_APPROVE) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    transaction.rejections[position] = true;
+    transaction.signers[position] = false;
+    transaction.vote_counts[position] = transaction.vote_counts[position].wrapping_add(1);
+
+    // reject once the remaining owners can no longer reach the threshold
+    let rejected_weight = multisig.weight_of(&transaction.rejections);
+    if multisig.total_weight() - rejected_weight < multisig.m as u32 {
+        transaction.status = TransactionStatus::Rejected;
+    }
+
+    // serializing
+    Transaction::pack(transaction, &mut transaction_info.data.borrow_mut())?;
 
-    invoke_signed(
-        &ix,
-        // Order doesn't matter and this slice could include all the accounts and be:
-        // `&accounts`
-        &[config_info.clone(), multisig_info.clone(), target_info.clone()],
-        &[&[b"You pass butter", &[bump]]],
-    )?; 
-
-    transaction.did_execute = true;
-    
     Ok(())
+}
+
+/// Cancel
+pub fn Cancel(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let transaction_info = next_account_info(account_info_iter)?;
+    let multisig_info = next_account_info(account_info_iter)?;
+    let your_info = next_account_info(account_info_iter)?;
+
+    check_account_owner(program_id, transaction_info)?;
+    check_account_owner(program_id, multisig_info)?;
+    if !your_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    // deserializing
+    let mut transaction = Transaction::unpack(&transaction_info.data.borrow())?;
+    let multisig = Multisig::unpack(&multisig_info.data.borrow())?;
+    check_transaction_multisig(&transaction, multisig_info)?;
+    check_transaction_active(&transaction)?;
+
+    // the proposer may always withdraw, other owners need the cancel role
+    if !cmp_pubkeys(&transaction.proposer, your_info.key) {
+        let position = multisig.position(your_info.key).ok_or(ProgramError::InvalidArgument)?;
+        if !multisig.has_role(position, ROLE_CANCEL) {
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
 
+    transaction.status = TransactionStatus::Cancelled;
+
+    // serializing
+    Transaction::pack(transaction, &mut transaction_info.data.borrow_mut())?;
+
+    Ok(())
 }
 
+/// Close Transaction
+pub fn CloseTransaction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let transaction_info = next_account_info(account_info_iter)?;
+    let proposer_info = next_account_info(account_info_iter)?;
+    let recipient_info = next_account_info(account_info_iter)?;
+
+    check_account_owner(program_id, transaction_info)?;
+    if cmp_pubkeys(transaction_info.key, recipient_info.key) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    // deserializing
+    let transaction = Transaction::unpack(&transaction_info.data.borrow())?;
+    validate_owner(&transaction.proposer, proposer_info)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let is_expired = transaction.expires_at.map_or(false, |expires_at| now >= expires_at);
+    if transaction.status == TransactionStatus::Active && !is_expired {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let lamports = transaction_info.lamports();
+    **recipient_info.lamports.borrow_mut() = recipient_info
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::InvalidArgument)?;
+    **transaction_info.lamports.borrow_mut() = 0;
+
+    // wipe the account and hand it back to the system program, so that it
+    // cannot be refunded and initialized again within the same transaction
+    let data_len = transaction_info.data_len();
+    sol_memset(*transaction_info.data.borrow_mut(), 0, data_len);
+    transaction_info.realloc(0, false)?;
+    transaction_info.assign(&system_program::id());
+
+    Ok(())
+}
+
+/// Set Owners
+pub fn SetOwners(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    owners: Vec<Pubkey>,
+    weights: Vec<u8>,
+    roles: Vec<u8>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let multisig_info = next_account_info(account_info_iter)?;
+
+    check_account_owner(program_id, multisig_info)?;
+    // only the multisig itself, through an executed transaction, may change its owners
+    if !multisig_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // deserializing
+    let mut multisig = Multisig::unpack(&multisig_info.data.borrow())?;
+
+    validate_owners(&owners)?;
+    validate_weights_and_roles(owners.len(), &weights, &roles)?;
+
+    multisig.signers = [Pubkey::default(); MAX_SIGNERS];
+    multisig.signers[..owners.len()].copy_from_slice(&owners);
+    multisig.weights = [0; MAX_SIGNERS];
+    multisig.weights[..weights.len()].copy_from_slice(&weights);
+    multisig.roles = [0; MAX_SIGNERS];
+    multisig.roles[..roles.len()].copy_from_slice(&roles);
+    multisig.n = owners.len() as u8;
+    if multisig.m as u32 > multisig.total_weight() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    // approvals of pending transactions refer to positions in the old owner set
+    multisig.owner_set_seqno = multisig.owner_set_seqno.wrapping_add(1);
+
+    // serializing
+    Multisig::pack(multisig, &mut multisig_info.data.borrow_mut())?;
+
+    Ok(())
+}
 
+/// Change Threshold
+pub fn ChangeThreshold(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    m: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let multisig_info = next_account_info(account_info_iter)?;
+
+    check_account_owner(program_id, multisig_info)?;
+    // only the multisig itself, through an executed transaction, may change its threshold
+    if !multisig_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // deserializing
+    let mut multisig = Multisig::unpack(&multisig_info.data.borrow())?;
+
+    if m == 0 || m as u32 > multisig.total_weight() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    multisig.m = m;
+
+    // serializing
+    Multisig::pack(multisig, &mut multisig_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+/// Checks that the owners are in range, non-zero and unique
+pub fn validate_owners(owners: &[Pubkey]) -> ProgramResult {
+    if !is_valid_signer_index(owners.len()) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    for (i, owner) in owners.iter().enumerate() {
+        if *owner == Pubkey::default() {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if owners[..i].iter().any(|other| cmp_pubkeys(other, owner)) {
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+    Ok(())
+}
+
+/// Checks that every owner has a non-zero weight and only known roles
+pub fn validate_weights_and_roles(n: usize, weights: &[u8], roles: &[u8]) -> ProgramResult {
+    if weights.len() != n || roles.len() != n {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if weights.contains(&0) || roles.iter().any(|role| role & !ROLE_ALL != 0) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(())
+}
+
+/// Checks that the transaction is still open for votes and execution
+pub fn check_transaction_active(transaction: &Transaction) -> ProgramResult {
+    if transaction.status != TransactionStatus::Active {
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(())
+}
+
+/// Checks that the transaction belongs to the given multisig
+pub fn check_transaction_multisig(
+    transaction: &Transaction,
+    multisig_info: &AccountInfo,
+) -> ProgramResult {
+    if !cmp_pubkeys(&transaction.multisig, multisig_info.key) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(())
+}
+
+/// Checks that the owners have not changed since the transaction was created
+pub fn check_transaction_seqno(transaction: &Transaction, multisig: &Multisig) -> ProgramResult {
+    if transaction.owner_set_seqno != multisig.owner_set_seqno {
+        msg!("Owners changed since the transaction was created");
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(())
+}
+
+/// Checks that `now` lies within the transaction's timelock and expiry
+pub fn check_transaction_window(transaction: &Transaction, now: i64) -> ProgramResult {
+    if transaction.valid_after.map_or(false, |valid_after| now < valid_after) {
+        msg!("Transaction is still timelocked");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if transaction.expires_at.map_or(false, |expires_at| now >= expires_at) {
+        msg!("Transaction has expired");
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(())
+}
+
+/// Validate Owner 
+pub fn validate_owner(
+    expected_owner: &Pubkey,
+    owner_account_info: &AccountInfo,
+) -> ProgramResult {
+    if !cmp_pubkeys(expected_owner, owner_account_info.key) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if !owner_account_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    Ok(())
+}
 
 /// Checks that the account is owned by the expected program
 pub fn check_account_owner(program_id: &Pubkey, account_info: &AccountInfo) -> ProgramResult {
@@ -4,25 +4,104 @@ use solana_program::{
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack, Sealed},
     pubkey::{Pubkey, PUBKEY_BYTES},
+    hash::{hashv, Hash},
+    instruction::Instruction,
     msg,
 };
 use crate::instruction::MAX_SIGNERS;
 
 
+/// Seed prefix of every multisig PDA, followed by the creator key and the nonce
+pub const MULTISIG_SEED: &[u8] = b"multisig";
+/// Seed prefix of every vault PDA, followed by the multisig key
+pub const VAULT_SEED: &[u8] = b"vault";
+
+/// Domain separator of the digest owners sign to approve a transaction offline
+pub const APPROVAL_DOMAIN: &[u8] = b"General-Multisig approval";
+
+/// Role allowing a signer to create transactions
+pub const ROLE_PROPOSE: u8 = 1 << 0;
+/// Role allowing a signer to approve and reject transactions
+pub const ROLE_APPROVE: u8 = 1 << 1;
+/// Role allowing a signer to execute approved transactions
+pub const ROLE_EXECUTE: u8 = 1 << 2;
+/// Role allowing a signer to cancel transactions proposed by others
+pub const ROLE_CANCEL: u8 = 1 << 3;
+/// All roles
+pub const ROLE_ALL: u8 = ROLE_PROPOSE | ROLE_APPROVE | ROLE_EXECUTE | ROLE_CANCEL;
 
 
 /// Multisig account 
 pub struct Multisig {
-    /// Number of signers required
-    pub m: u8,
+    /// Total weight of approvals required
+    pub m: u16,
     /// Number of valid signers
     pub n: u8,
     /// Is `true` if this structure has been initialized
     pub is_initialized: bool,
     /// Signer public keys
     pub signers: [Pubkey; MAX_SIGNERS],
+    /// Voting weight of each signer
+    pub weights: [u8; MAX_SIGNERS],
+    /// Role bitmask of each signer
+    pub roles: [u8; MAX_SIGNERS],
+    /// The key that created the multisig, used to derive its address
+    pub creator: Pubkey,
+    /// Nonce chosen by the creator, used to derive its address
+    pub nonce: u64,
+    /// Bump seed of the multisig address
+    pub bump: u8,
+    /// Bump seed of the vault address
+    pub vault_bump: u8,
+    /// Incremented every time the owners change, invalidating pending transactions
+    pub owner_set_seqno: u32,
+}
+
+impl Multisig {
+    /// Finds the multisig address for the given creator and nonce
+    pub fn find_address(program_id: &Pubkey, creator: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[MULTISIG_SEED, creator.as_ref(), &nonce.to_le_bytes()],
+            program_id,
+        )
+    }
+
+    /// Finds the address of the system-owned vault holding the multisig's lamports
+    pub fn find_vault_address(program_id: &Pubkey, multisig: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[VAULT_SEED, multisig.as_ref()], program_id)
+    }
+
+    /// Sums the weights of the signers whose flag is set
+    pub fn weight_of(&self, flags: &[bool; MAX_SIGNERS]) -> u32 {
+        self.weights[0..self.n as usize]
+            .iter()
+            .zip(flags.iter())
+            .filter(|(_, flag)| **flag)
+            .map(|(weight, _)| *weight as u32)
+            .sum()
+    }
+
+    /// Sums the weights of all signers allowed to approve
+    pub fn total_weight(&self) -> u32 {
+        let mut approvers = [false; MAX_SIGNERS];
+        for (position, approver) in approvers[0..self.n as usize].iter_mut().enumerate() {
+            *approver = self.has_role(position, ROLE_APPROVE);
+        }
+        self.weight_of(&approvers)
+    }
+
+    /// Returns the position of `key` among the signers
+    pub fn position(&self, key: &Pubkey) -> Option<usize> {
+        self.signers[0..self.n as usize].iter().position(|signer| signer == key)
+    }
+
+    /// Returns `true` if the signer at `position` holds every bit of `role`
+    pub fn has_role(&self, position: usize, role: u8) -> bool {
+        self.roles[position] & role == role
+    }
 }
 
+
 impl Sealed for Multisig {}
 impl IsInitialized for Multisig {
     fn is_initialized(&self) -> bool {
@@ -31,13 +110,25 @@ impl IsInitialized for Multisig {
 }
 
 impl Pack for Multisig {
-    const LEN: usize = 1024;
+    const LEN: usize = 424;
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let src = array_ref![src, 0, 355];
+        let src = array_ref![src, 0, Multisig::LEN];
         #[allow(clippy::ptr_offset_with_cast)]
-        let (m, n, is_initialized, signers_flat) = array_refs![src, 1, 1, 1, 32 * MAX_SIGNERS];
+        let (
+            m,
+            n,
+            is_initialized,
+            signers_flat,
+            weights,
+            roles,
+            creator,
+            nonce,
+            bump,
+            vault_bump,
+            owner_set_seqno,
+        ) = array_refs![src, 2, 1, 1, 32 * MAX_SIGNERS, MAX_SIGNERS, MAX_SIGNERS, 32, 8, 1, 1, 4];
         let mut result = Multisig {
-            m: m[0],
+            m: u16::from_le_bytes(*m),
             n: n[0],
             is_initialized: match is_initialized {
                 [0] => false,
@@ -45,6 +136,13 @@ impl Pack for Multisig {
                 _ => return Err(ProgramError::InvalidAccountData),
             },
             signers: [Pubkey::new_from_array([0u8; 32]); MAX_SIGNERS],
+            weights: *weights,
+            roles: *roles,
+            creator: Pubkey::new_from_array(*creator),
+            nonce: u64::from_le_bytes(*nonce),
+            bump: bump[0],
+            vault_bump: vault_bump[0],
+            owner_set_seqno: u32::from_le_bytes(*owner_set_seqno),
         };
         for (src, dst) in signers_flat.chunks(32).zip(result.signers.iter_mut()) {
             *dst = Pubkey::new(src);
@@ -52,46 +150,314 @@ impl Pack for Multisig {
         Ok(result)
     }
     fn pack_into_slice(&self, dst: &mut [u8]) {
-        let dst = array_mut_ref![dst, 0, 355];
+        let dst = array_mut_ref![dst, 0, Multisig::LEN];
         #[allow(clippy::ptr_offset_with_cast)]
-        let (m, n, is_initialized, signers_flat) = mut_array_refs![dst, 1, 1, 1, 32 * MAX_SIGNERS];
-        *m = [self.m];
+        let (
+            m,
+            n,
+            is_initialized,
+            signers_flat,
+            weights,
+            roles,
+            creator,
+            nonce,
+            bump,
+            vault_bump,
+            owner_set_seqno,
+        ) = mut_array_refs![dst, 2, 1, 1, 32 * MAX_SIGNERS, MAX_SIGNERS, MAX_SIGNERS, 32, 8, 1, 1, 4];
+        *m = self.m.to_le_bytes();
         *n = [self.n];
         *is_initialized = [self.is_initialized as u8];
         for (i, src) in self.signers.iter().enumerate() {
             let dst_array = array_mut_ref![signers_flat, 32 * i, 32];
             dst_array.copy_from_slice(src.as_ref());
         }
+        *weights = self.weights;
+        *roles = self.roles;
+        creator.copy_from_slice(self.creator.as_ref());
+        *nonce = self.nonce.to_le_bytes();
+        *bump = [self.bump];
+        *vault_bump = [self.vault_bump];
+        *owner_set_seqno = self.owner_set_seqno.to_le_bytes();
     }
 }
 
 /// Transaction Account
-#[derive(BorshSerialize, BorshDeserialize, Debug)] 
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)] 
 pub struct TransactionAccount {
     pub pubkey: Pubkey,
     pub is_signer: bool,
     pub is_writable: bool,
 }
 
+impl TransactionAccount {
+    /// Size of a packed TransactionAccount
+    pub const LEN: usize = PUBKEY_BYTES + 1 + 1;
 
-/// Multisig account 
-pub struct Transaction {
-    /// The multisig account this transaction belongs to.
-    pub multisig: Pubkey,
+    /// Unpacks a u16 length-prefixed list of accounts, returning the remaining input
+    pub fn unpack_list(input: &[u8]) -> Result<(Vec<Self>, &[u8]), ProgramError> {
+        let (len, rest) = unpack_len(input)?;
+        let size = len.checked_mul(Self::LEN).ok_or(ProgramError::InvalidAccountData)?;
+        if rest.len() < size {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let (src, rest) = rest.split_at(size);
+        let mut accounts = Vec::with_capacity(len);
+        for chunk in src.chunks(Self::LEN) {
+            let chunk = array_ref![chunk, 0, TransactionAccount::LEN];
+            let (pubkey, is_signer, is_writable) = array_refs![chunk, PUBKEY_BYTES, 1, 1];
+            accounts.push(TransactionAccount {
+                pubkey: Pubkey::new_from_array(*pubkey),
+                is_signer: unpack_bool(is_signer)?,
+                is_writable: unpack_bool(is_writable)?,
+            });
+        }
+        Ok((accounts, rest))
+    }
+
+    /// Packs a u16 length-prefixed list of accounts, returning the number of bytes written
+    pub fn pack_list(accounts: &[Self], dst: &mut [u8]) -> usize {
+        let mut offset = pack_len(accounts.len(), dst);
+        for account in accounts.iter() {
+            let chunk = array_mut_ref![dst, offset, TransactionAccount::LEN];
+            let (pubkey, is_signer, is_writable) = mut_array_refs![chunk, PUBKEY_BYTES, 1, 1];
+            pubkey.copy_from_slice(account.pubkey.as_ref());
+            *is_signer = [account.is_signer as u8];
+            *is_writable = [account.is_writable as u8];
+            offset += Self::LEN;
+        }
+        offset
+    }
+}
+
+/// Instruction executed by a transaction
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransactionInstruction {
     /// Target program to execute against.
     pub program_id: Pubkey,
     /// accounts passed to target program 
-    pub accounts: [TransactionAccount; 2],
+    pub accounts: Vec<TransactionAccount>,
     /// instruction data 
-    pub data: u8,
+    pub data: Vec<u8>,
+}
+
+impl TransactionInstruction {
+    /// Number of bytes the instruction occupies once packed
+    pub fn packed_len(&self) -> usize {
+        PUBKEY_BYTES + 2 + self.accounts.len() * TransactionAccount::LEN + 2 + self.data.len()
+    }
+
+    /// Unpacks a u16 length-prefixed list of instructions, returning the remaining input
+    pub fn unpack_list(input: &[u8]) -> Result<(Vec<Self>, &[u8]), ProgramError> {
+        let (len, mut rest) = unpack_len(input)?;
+        let mut instructions = Vec::new();
+        for _ in 0..len {
+            if rest.len() < PUBKEY_BYTES {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let (program_id, _rest) = rest.split_at(PUBKEY_BYTES);
+            let (accounts, _rest) = TransactionAccount::unpack_list(_rest)?;
+            let (data, _rest) = unpack_bytes(_rest)?;
+            instructions.push(TransactionInstruction {
+                program_id: Pubkey::new_from_array(*array_ref![program_id, 0, PUBKEY_BYTES]),
+                accounts,
+                data,
+            });
+            rest = _rest;
+        }
+        Ok((instructions, rest))
+    }
+
+    /// Packs a u16 length-prefixed list of instructions, returning the number of bytes written
+    pub fn pack_list(instructions: &[Self], dst: &mut [u8]) -> usize {
+        let mut offset = pack_len(instructions.len(), dst);
+        for instruction in instructions.iter() {
+            dst[offset..offset + PUBKEY_BYTES].copy_from_slice(instruction.program_id.as_ref());
+            offset += PUBKEY_BYTES;
+            offset += TransactionAccount::pack_list(&instruction.accounts, &mut dst[offset..]);
+            offset += pack_bytes(&instruction.data, &mut dst[offset..]);
+        }
+        offset
+    }
+}
+
+impl From<Instruction> for TransactionInstruction {
+    fn from(instruction: Instruction) -> Self {
+        TransactionInstruction {
+            program_id: instruction.program_id,
+            accounts: instruction
+                .accounts
+                .iter()
+                .map(|meta| TransactionAccount {
+                    pubkey: meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: instruction.data,
+        }
+    }
+}
+
+/// Unpacks u16 length-prefixed bytes, returning the remaining input
+pub fn unpack_bytes(input: &[u8]) -> Result<(Vec<u8>, &[u8]), ProgramError> {
+    let (len, rest) = unpack_len(input)?;
+    if rest.len() < len {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let (data, rest) = rest.split_at(len);
+    Ok((data.to_vec(), rest))
+}
+
+/// Packs u16 length-prefixed bytes, returning the number of bytes written
+pub fn pack_bytes(data: &[u8], dst: &mut [u8]) -> usize {
+    let offset = pack_len(data.len(), dst);
+    dst[offset..offset + data.len()].copy_from_slice(data);
+    offset + data.len()
+}
+
+fn unpack_len(input: &[u8]) -> Result<(usize, &[u8]), ProgramError> {
+    if input.len() < 2 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let (len, rest) = input.split_at(2);
+    Ok((u16::from_le_bytes([len[0], len[1]]) as usize, rest))
+}
+
+fn pack_len(len: usize, dst: &mut [u8]) -> usize {
+    dst[..2].copy_from_slice(&(len as u16).to_le_bytes());
+    2
+}
+
+/// Unpacks an optional i64 stored as a presence flag followed by the value
+pub fn unpack_option_i64(src: &[u8; 9]) -> Result<Option<i64>, ProgramError> {
+    let (tag, value) = array_refs![src, 1, 8];
+    Ok(match unpack_bool(tag)? {
+        true => Some(i64::from_le_bytes(*value)),
+        false => None,
+    })
+}
+
+/// Packs an optional i64 as a presence flag followed by the value
+pub fn pack_option_i64(src: Option<i64>, dst: &mut [u8; 9]) {
+    let (tag, value) = mut_array_refs![dst, 1, 8];
+    *tag = [src.is_some() as u8];
+    *value = src.unwrap_or_default().to_le_bytes();
+}
+
+fn unpack_bool(src: &[u8; 1]) -> Result<bool, ProgramError> {
+    match src {
+        [0] => Ok(false),
+        [1] => Ok(true),
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+
+/// Lifecycle of a transaction
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransactionStatus {
+    /// Open for approvals and rejections
+    Active,
+    /// Executed by the multisig
+    Executed,
+    /// Withdrawn by the proposer
+    Cancelled,
+    /// Rejected by enough owners that it can no longer be approved
+    Rejected,
+}
+
+impl TransactionStatus {
+    fn unpack(src: &[u8; 1]) -> Result<Self, ProgramError> {
+        match src {
+            [0] => Ok(TransactionStatus::Active),
+            [1] => Ok(TransactionStatus::Executed),
+            [2] => Ok(TransactionStatus::Cancelled),
+            [3] => Ok(TransactionStatus::Rejected),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+
+/// Multisig account 
+pub struct Transaction {
+    /// The multisig account this transaction belongs to.
+    pub multisig: Pubkey,
+    /// The owner that created the transaction and may cancel it.
+    pub proposer: Pubkey,
+    /// Instructions executed in order, all or nothing.
+    pub instructions: Vec<TransactionInstruction>,
     /// signers[index] is true iff multisig.owners[index] signed the transaction.
     pub signers: [bool; MAX_SIGNERS],
-    /// Boolean ensuring one time execution.
-    pub did_execute: bool,
+    /// rejections[index] is true iff multisig.owners[index] rejected the transaction.
+    pub rejections: [bool; MAX_SIGNERS],
+    /// vote_counts[index] is bumped whenever multisig.owners[index] unapproves
+    /// or rejects, voiding the approvals that owner signed before.
+    pub vote_counts: [u16; MAX_SIGNERS],
+    /// Lifecycle status, ensuring one time execution.
+    pub status: TransactionStatus,
+    /// Unix timestamp before which the transaction cannot be executed
+    pub valid_after: Option<i64>,
+    /// Unix timestamp from which the transaction can no longer be executed
+    pub expires_at: Option<i64>,
+    /// The multisig's owner_set_seqno when the transaction was created
+    pub owner_set_seqno: u32,
     /// Is `true` if this structure has been initialized
     pub is_initialized: bool,
 }
 
+impl Transaction {
+    /// Size of the fixed part, followed by the length-prefixed instructions
+    pub const HEADER_LEN: usize = 32 + 32 + MAX_SIGNERS + MAX_SIGNERS + 2 * MAX_SIGNERS + 1 + 9 + 9 + 4 + 1;
+    /// Space reserved after the header for the length-prefixed instructions
+    pub const INSTRUCTIONS_LEN: usize = 1024;
+
+    /// Number of bytes the transaction occupies once packed
+    pub fn packed_len(&self) -> usize {
+        Self::HEADER_LEN
+            + 2
+            + self.instructions.iter().map(|instruction| instruction.packed_len()).sum::<usize>()
+    }
+
+    /// Digest `owner` signs with ed25519 to approve the transaction stored
+    /// at `transaction` without submitting a transaction themselves. It
+    /// commits to everything the approval vouches for, so a signature cannot
+    /// be replayed on another program, account, owner set or proposal.
+    /// `vote_count` is the owner's entry in `vote_counts`, so a signature
+    /// cannot be replayed after the owner unapproves or rejects either.
+    pub fn approval_digest(
+        &self,
+        program_id: &Pubkey,
+        transaction: &Pubkey,
+        owner: &Pubkey,
+        vote_count: u16,
+    ) -> Hash {
+        let mut instructions = vec![0; self.packed_len() - Self::HEADER_LEN];
+        TransactionInstruction::pack_list(&self.instructions, &mut instructions);
+        let mut valid_after = [0; 9];
+        pack_option_i64(self.valid_after, &mut valid_after);
+        let mut expires_at = [0; 9];
+        pack_option_i64(self.expires_at, &mut expires_at);
+        hashv(&[
+            APPROVAL_DOMAIN,
+            program_id.as_ref(),
+            transaction.as_ref(),
+            self.multisig.as_ref(),
+            self.proposer.as_ref(),
+            &self.owner_set_seqno.to_le_bytes(),
+            owner.as_ref(),
+            &vote_count.to_le_bytes(),
+            &valid_after,
+            &expires_at,
+            &instructions,
+        ])
+    }
+}
+
+// accounts of both types are owned by the program, so their sizes must tell them apart
+const _: () = assert!(Transaction::LEN != Multisig::LEN);
+
 impl Sealed for Transaction {}
 impl IsInitialized for Transaction {
     fn is_initialized(&self) -> bool { 
@@ -100,79 +466,47 @@ impl IsInitialized for Transaction {
 }
 
 impl Pack for Transaction {
-    const LEN: usize = 1024;
+    const LEN: usize = Transaction::HEADER_LEN + Transaction::INSTRUCTIONS_LEN;
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let src = array_ref![src, 0, 146];
+        let (header, tail) = src.split_at(Transaction::HEADER_LEN);
+        let header = array_ref![header, 0, Transaction::HEADER_LEN];
         #[allow(clippy::ptr_offset_with_cast)]
         let (
             multisig, 
-            program_id, 
-            pubkey1,
-            is_signer1,
-            is_writable1,
-            pubkey2,
-            is_signer2,
-            is_writable2, 
-            data, 
+            proposer,
             signers_flat, 
-            did_execute, 
+            rejections_flat,
+            vote_counts_flat,
+            status, 
+            valid_after,
+            expires_at,
+            owner_set_seqno,
             is_initialized
         ) = array_refs![
-            src, 
-            32, 
+            header, 
             32, 
             32,
-            1,
-            1,
-            32,
-            1,
-            1, 
-            1, 
             MAX_SIGNERS, 
+            MAX_SIGNERS,
+            2 * MAX_SIGNERS,
             1, 
+            9,
+            9,
+            4,
             1];
-        let mut t1 = TransactionAccount {
-            pubkey: Pubkey::new_from_array(*pubkey1),
-            is_signer: match is_signer1 {
-                [0] => false,
-                [1] => true,
-                _ => return Err(ProgramError::InvalidAccountData),
-            },
-            is_writable: match is_writable1 {
-                [0] => false,
-                [1] => true,
-                _ => return Err(ProgramError::InvalidAccountData),
-            },
-        };
-        let mut t2 = TransactionAccount {
-            pubkey: Pubkey::new_from_array(*pubkey2),
-            is_signer: match is_signer2 {
-                [0] => false,
-                [1] => true,
-                _ => return Err(ProgramError::InvalidAccountData),
-            },
-            is_writable: match is_writable2 {
-                [0] => false,
-                [1] => true,
-                _ => return Err(ProgramError::InvalidAccountData),
-            },
-        };
+        let (instructions, _tail) = TransactionInstruction::unpack_list(tail)?;
         let mut result = Transaction {
             multisig: Pubkey::new_from_array(*multisig),
-            program_id: Pubkey::new_from_array(*program_id),
-            accounts: [t1, t2],
-            data: data[0],
+            proposer: Pubkey::new_from_array(*proposer),
+            instructions,
             signers: [false; MAX_SIGNERS],
-            did_execute: match did_execute {
-                [0] => false,
-                [1] => true,
-                _ => return Err(ProgramError::InvalidAccountData),
-            },
-            is_initialized: match is_initialized {
-                [0] => false,
-                [1] => true,
-                _ => return Err(ProgramError::InvalidAccountData),
-            },
+            rejections: [false; MAX_SIGNERS],
+            vote_counts: [0; MAX_SIGNERS],
+            status: TransactionStatus::unpack(status)?,
+            valid_after: unpack_option_i64(valid_after)?,
+            expires_at: unpack_option_i64(expires_at)?,
+            owner_set_seqno: u32::from_le_bytes(*owner_set_seqno),
+            is_initialized: unpack_bool(is_initialized)?,
         };
         for (src, dst) in signers_flat.chunks(1).zip(result.signers.iter_mut()) {
             if src == &[0] {
@@ -181,55 +515,59 @@ impl Pack for Transaction {
                *dst = true;
             }
         }
+        for (src, dst) in rejections_flat.iter().zip(result.rejections.iter_mut()) {
+            *dst = *src != 0;
+        }
+        for (src, dst) in vote_counts_flat.chunks(2).zip(result.vote_counts.iter_mut()) {
+            *dst = u16::from_le_bytes(*array_ref![src, 0, 2]);
+        }
         Ok(result)
     }
     fn pack_into_slice(&self, dst: &mut [u8]) {
-        let dst = array_mut_ref![dst, 0, 146];
+        let (header, tail) = dst.split_at_mut(Transaction::HEADER_LEN);
+        let header = array_mut_ref![header, 0, Transaction::HEADER_LEN];
         #[allow(clippy::ptr_offset_with_cast)]
         let (
             multisig, 
-            program_id, 
-            pubkey1,
-            is_signer1,
-            is_writable1,
-            pubkey2,
-            is_signer2,
-            is_writable2, 
-            data, 
+            proposer,
             signers_flat, 
-            did_execute, 
+            rejections_flat,
+            vote_counts_flat,
+            status, 
+            valid_after,
+            expires_at,
+            owner_set_seqno,
             is_initialized
         ) = mut_array_refs![
-            dst,
-            32, 
+            header,
             32, 
             32,
-            1,
-            1,
-            32,
-            1,
-            1, 
-            1, 
             MAX_SIGNERS, 
+            MAX_SIGNERS,
+            2 * MAX_SIGNERS,
             1, 
+            9,
+            9,
+            4,
             1
         ];
         multisig.copy_from_slice(self.multisig.as_ref());
-        program_id.copy_from_slice(self.program_id.as_ref());
-        pubkey1.copy_from_slice(self.accounts[0].pubkey.as_ref());
-        *is_signer1 = [self.accounts[0].is_signer as u8];
-        *is_writable1 = [self.accounts[0].is_writable as u8];
-        pubkey2.copy_from_slice(self.accounts[1].pubkey.as_ref());
-        *is_signer2 = [self.accounts[1].is_signer as u8];
-        *is_writable2 = [self.accounts[1].is_writable as u8];
-        *data = [self.data as u8];
+        proposer.copy_from_slice(self.proposer.as_ref());
         for (i, src) in self.signers.iter().enumerate() {
             let dst_array = array_mut_ref![signers_flat, i, 1];
             dst_array.copy_from_slice(&[*src as u8]);
         }
-        *did_execute = [self.did_execute as u8];
+        for (src, dst) in self.rejections.iter().zip(rejections_flat.iter_mut()) {
+            *dst = *src as u8;
+        }
+        for (src, dst) in self.vote_counts.iter().zip(vote_counts_flat.chunks_mut(2)) {
+            dst.copy_from_slice(&src.to_le_bytes());
+        }
+        *status = [self.status as u8];
+        pack_option_i64(self.valid_after, valid_after);
+        pack_option_i64(self.expires_at, expires_at);
+        *owner_set_seqno = self.owner_set_seqno.to_le_bytes();
         *is_initialized = [self.is_initialized as u8];
+        TransactionInstruction::pack_list(&self.instructions, tail);
     }
 }
-
-
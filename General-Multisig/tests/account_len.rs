@@ -0,0 +1,72 @@
+mod common;
+
+use common::*;
+use solana_program::{instruction::Instruction, program_pack::Pack, pubkey::Pubkey, system_instruction};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    instruction::InstructionError,
+    signature::{Keypair, Signer},
+};
+use General_Multisig::{
+    instruction::create_transaction,
+    state::{self, ROLE_ALL},
+};
+
+/// Creates a multisig owned by the payer alone
+async fn setup() -> (ProgramTestContext, Pubkey, Pubkey) {
+    let (mut context, program_id) = start().await;
+    let payer = context.payer.pubkey();
+    let multisig = create_multisig(&mut context, program_id, &[&payer], 1, &[1], &[ROLE_ALL]).await;
+
+    (context, program_id, multisig)
+}
+
+/// Proposes a transaction stored in `transaction`
+fn propose_into(program_id: Pubkey, multisig: Pubkey, transaction: Pubkey, proposer: Pubkey) -> Instruction {
+    create_transaction(
+        &program_id,
+        &transaction,
+        &multisig,
+        &proposer,
+        change_threshold_instructions(program_id, multisig, 1),
+        None,
+        None,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_create_transaction_exact_len() {
+    let (mut context, program_id, multisig) = setup().await;
+    let payer = context.payer.pubkey();
+    let rent = context.banks_client.get_rent().await.unwrap();
+
+    for len in [state::Transaction::LEN - 1, state::Transaction::LEN + 1, state::Transaction::LEN] {
+        let transaction = Keypair::new();
+        let instructions = [
+            system_instruction::create_account(
+                &payer,
+                &transaction.pubkey(),
+                rent.minimum_balance(len),
+                len as u64,
+                &program_id,
+            ),
+            propose_into(program_id, multisig, transaction.pubkey(), payer),
+        ];
+        let result = process(&mut context, &instructions, &[&transaction]).await;
+        if len == state::Transaction::LEN {
+            result.unwrap();
+        } else {
+            assert_instruction_error(result, InstructionError::InvalidAccountData);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_multisig_is_not_a_transaction() {
+    let (mut context, program_id, multisig) = setup().await;
+    let payer = context.payer.pubkey();
+
+    let result = process(&mut context, &[propose_into(program_id, multisig, multisig, payer)], &[]).await;
+    assert_instruction_error(result, InstructionError::InvalidAccountData);
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use common::*;
+use solana_program::{program_pack::Pack, system_instruction};
+use solana_sdk::signature::Signer;
+use General_Multisig::{instruction::allocate_pda, state::Multisig};
+
+/// Allocates the multisig of the payer, optionally sending `prefund`
+/// lamports to its address first, and checks it is rent-exempt and sized
+async fn allocate(prefund: u64) {
+    let (mut context, program_id) = start().await;
+    let payer = context.payer.pubkey();
+    let (multisig, _) = Multisig::find_address(&program_id, &payer, NONCE);
+    let rent = context.banks_client.get_rent().await.unwrap();
+
+    let mut instructions = Vec::new();
+    if prefund > 0 {
+        instructions.push(system_instruction::transfer(&payer, &multisig, prefund));
+    }
+    instructions.push(allocate_pda(&program_id, &payer, NONCE).unwrap());
+    process(&mut context, &instructions, &[]).await.unwrap();
+
+    let account = context
+        .banks_client
+        .get_account(multisig)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.owner, program_id);
+    assert_eq!(account.data.len(), Multisig::LEN);
+    assert_eq!(
+        account.lamports,
+        prefund.max(rent.minimum_balance(Multisig::LEN))
+    );
+}
+
+#[tokio::test]
+async fn test_allocate_rent_exempt() {
+    allocate(0).await;
+}
+
+#[tokio::test]
+async fn test_allocate_prefunded() {
+    allocate(1_000).await;
+}
+
+#[tokio::test]
+async fn test_allocate_overfunded() {
+    allocate(1_000_000_000).await;
+}
@@ -0,0 +1,183 @@
+mod common;
+
+use common::*;
+use solana_program::{hash::Hash, instruction::Instruction, pubkey::Pubkey};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    ed25519_instruction::new_ed25519_instruction,
+    instruction::InstructionError,
+    signature::{Keypair, Signer},
+};
+use General_Multisig::{
+    instruction::{approve_with_signatures, execute_transaction, reject, unapprove},
+    state::{ROLE_APPROVE, ROLE_EXECUTE, ROLE_PROPOSE},
+};
+
+/// Creates a multisig with a threshold of two, where the payer may only
+/// propose and execute and both `approvers` may only approve
+async fn setup(approvers: &[&Keypair]) -> (ProgramTestContext, Pubkey, Pubkey) {
+    let (mut context, program_id) = start().await;
+    let payer = context.payer.pubkey();
+    let approver_keys: Vec<Pubkey> = approvers.iter().map(|approver| approver.pubkey()).collect();
+    let mut owners = vec![&payer];
+    owners.extend(approver_keys.iter());
+    let weights = vec![1; owners.len()];
+    let mut roles = vec![ROLE_PROPOSE | ROLE_EXECUTE];
+    roles.resize(owners.len(), ROLE_APPROVE);
+    let multisig = create_multisig(&mut context, program_id, &owners, 2, &weights, &roles).await;
+
+    (context, program_id, multisig)
+}
+
+/// Proposes lowering the threshold to one
+async fn propose_change_threshold(
+    context: &mut ProgramTestContext,
+    program_id: Pubkey,
+    multisig: Pubkey,
+) -> Pubkey {
+    let instructions = change_threshold_instructions(program_id, multisig, 1);
+    propose(context, program_id, multisig, instructions, None, None).await
+}
+
+/// Digest `owner` signs to approve `transaction` in its current state
+async fn approval_digest(
+    context: &mut ProgramTestContext,
+    program_id: Pubkey,
+    multisig: Pubkey,
+    transaction: Pubkey,
+    owner: &Keypair,
+) -> Hash {
+    let position = load_multisig(context, multisig).await.position(&owner.pubkey());
+    let proposal = load_transaction(context, transaction).await;
+    let vote_count = position.map_or(0, |position| proposal.vote_counts[position]);
+    proposal.approval_digest(&program_id, &transaction, &owner.pubkey(), vote_count)
+}
+
+/// Signs `message` offline with `owner`'s key
+fn sign(owner: &Keypair, message: &[u8]) -> Instruction {
+    let keypair = ed25519_dalek::Keypair::from_bytes(&owner.to_bytes()).unwrap();
+    new_ed25519_instruction(&keypair, message)
+}
+
+fn execute(program_id: Pubkey, multisig: Pubkey, transaction: Pubkey, executor: Pubkey) -> Instruction {
+    let instructions = change_threshold_instructions(program_id, multisig, 1);
+    execute_transaction(&program_id, &transaction, &multisig, &executor, &instructions).unwrap()
+}
+
+#[tokio::test]
+async fn test_relay_signed_approvals() {
+    let (alice, bob) = (Keypair::new(), Keypair::new());
+    let (mut context, program_id, multisig) = setup(&[&alice, &bob]).await;
+    let payer = context.payer.pubkey();
+    let transaction = propose_change_threshold(&mut context, program_id, multisig).await;
+    let alice_digest = approval_digest(&mut context, program_id, multisig, transaction, &alice).await;
+    let bob_digest = approval_digest(&mut context, program_id, multisig, transaction, &bob).await;
+
+    // neither approver submits or signs a transaction
+    let instructions = [
+        sign(&alice, alice_digest.as_ref()),
+        sign(&bob, bob_digest.as_ref()),
+        approve_with_signatures(&program_id, &transaction, &multisig).unwrap(),
+    ];
+    process(&mut context, &instructions, &[]).await.unwrap();
+    let approvals = load_transaction(&mut context, transaction).await.signers;
+    assert_eq!(&approvals[0..3], &[false, true, true]);
+
+    process(&mut context, &[execute(program_id, multisig, transaction, payer)], &[])
+        .await
+        .unwrap();
+    assert_eq!(threshold(&mut context, multisig).await, 1);
+}
+
+#[tokio::test]
+async fn test_reject_signature_over_other_transaction() {
+    let (alice, bob) = (Keypair::new(), Keypair::new());
+    let (mut context, program_id, multisig) = setup(&[&alice, &bob]).await;
+    let transaction = propose_change_threshold(&mut context, program_id, multisig).await;
+    let other = propose_change_threshold(&mut context, program_id, multisig).await;
+    let other_digest = approval_digest(&mut context, program_id, multisig, other, &alice).await;
+
+    let instructions = [
+        sign(&alice, other_digest.as_ref()),
+        approve_with_signatures(&program_id, &transaction, &multisig).unwrap(),
+    ];
+    let result = process(&mut context, &instructions, &[]).await;
+    assert_instruction_error(result, InstructionError::MissingRequiredSignature);
+    let approvals = load_transaction(&mut context, transaction).await.signers;
+    assert_eq!(&approvals[0..3], &[false, false, false]);
+}
+
+#[tokio::test]
+async fn test_reject_signature_of_non_owner() {
+    let (alice, bob) = (Keypair::new(), Keypair::new());
+    let (mut context, program_id, multisig) = setup(&[&alice, &bob]).await;
+    let transaction = propose_change_threshold(&mut context, program_id, multisig).await;
+    let stranger = Keypair::new();
+    let alice_digest = approval_digest(&mut context, program_id, multisig, transaction, &alice).await;
+    let stranger_digest = approval_digest(&mut context, program_id, multisig, transaction, &stranger).await;
+
+    let instructions = [
+        sign(&alice, alice_digest.as_ref()),
+        sign(&stranger, stranger_digest.as_ref()),
+        approve_with_signatures(&program_id, &transaction, &multisig).unwrap(),
+    ];
+    let result = process(&mut context, &instructions, &[]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_reject_signature_replayed_after_unapprove() {
+    let (alice, bob) = (Keypair::new(), Keypair::new());
+    let (mut context, program_id, multisig) = setup(&[&alice, &bob]).await;
+    let transaction = propose_change_threshold(&mut context, program_id, multisig).await;
+    let digest = approval_digest(&mut context, program_id, multisig, transaction, &alice).await;
+    let signature = sign(&alice, digest.as_ref());
+
+    let instructions = [
+        signature.clone(),
+        approve_with_signatures(&program_id, &transaction, &multisig).unwrap(),
+    ];
+    process(&mut context, &instructions, &[]).await.unwrap();
+    let instructions = [unapprove(&program_id, &transaction, &multisig, &alice.pubkey()).unwrap()];
+    process(&mut context, &instructions, &[&alice]).await.unwrap();
+
+    // anyone can copy the signature from the chain and relay it again
+    refresh_blockhash(&mut context).await;
+    let instructions = [
+        signature,
+        approve_with_signatures(&program_id, &transaction, &multisig).unwrap(),
+    ];
+    let result = process(&mut context, &instructions, &[]).await;
+    assert_instruction_error(result, InstructionError::MissingRequiredSignature);
+    let approvals = load_transaction(&mut context, transaction).await.signers;
+    assert_eq!(&approvals[0..3], &[false, false, false]);
+}
+
+#[tokio::test]
+async fn test_reject_signature_replayed_after_reject() {
+    let (alice, bob, carol) = (Keypair::new(), Keypair::new(), Keypair::new());
+    // a third approver keeps the proposal reachable after bob rejects
+    let (mut context, program_id, multisig) = setup(&[&alice, &bob, &carol]).await;
+    let transaction = propose_change_threshold(&mut context, program_id, multisig).await;
+    let digest = approval_digest(&mut context, program_id, multisig, transaction, &bob).await;
+    let signature = sign(&bob, digest.as_ref());
+
+    let instructions = [
+        signature.clone(),
+        approve_with_signatures(&program_id, &transaction, &multisig).unwrap(),
+    ];
+    process(&mut context, &instructions, &[]).await.unwrap();
+    let instructions = [reject(&program_id, &transaction, &multisig, &bob.pubkey()).unwrap()];
+    process(&mut context, &instructions, &[&bob]).await.unwrap();
+
+    refresh_blockhash(&mut context).await;
+    let instructions = [
+        signature,
+        approve_with_signatures(&program_id, &transaction, &multisig).unwrap(),
+    ];
+    let result = process(&mut context, &instructions, &[]).await;
+    assert_instruction_error(result, InstructionError::MissingRequiredSignature);
+    let proposal = load_transaction(&mut context, transaction).await;
+    assert_eq!(&proposal.signers[0..4], &[false, false, false, false]);
+    assert_eq!(&proposal.rejections[0..4], &[false, false, true, false]);
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use common::*;
+use solana_program::{program_pack::Pack, pubkey::Pubkey, system_instruction, system_program};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{instruction::InstructionError, signature::Signer};
+use General_Multisig::{
+    instruction::{cancel, close_transaction, create_transaction},
+    state::{self, ROLE_ALL},
+};
+
+/// Creates a multisig owned by the payer alone and a cancelled proposal
+async fn setup() -> (ProgramTestContext, Pubkey, Pubkey, Pubkey) {
+    let (mut context, program_id) = start().await;
+    let payer = context.payer.pubkey();
+    let multisig = create_multisig(&mut context, program_id, &[&payer], 1, &[1], &[ROLE_ALL]).await;
+    let instructions = change_threshold_instructions(program_id, multisig, 1);
+    let transaction = propose(&mut context, program_id, multisig, instructions, None, None).await;
+    let instructions = [cancel(&program_id, &transaction, &multisig, &payer).unwrap()];
+    process(&mut context, &instructions, &[]).await.unwrap();
+
+    (context, program_id, multisig, transaction)
+}
+
+#[tokio::test]
+async fn test_close_refunds_recipient() {
+    let (mut context, program_id, _multisig, transaction) = setup().await;
+    let payer = context.payer.pubkey();
+    let recipient = Pubkey::new_unique();
+    let rent = context.banks_client.get_rent().await.unwrap();
+
+    let instructions = [close_transaction(&program_id, &transaction, &payer, &recipient).unwrap()];
+    process(&mut context, &instructions, &[]).await.unwrap();
+
+    let refunded = context.banks_client.get_balance(recipient).await.unwrap();
+    assert_eq!(refunded, rent.minimum_balance(state::Transaction::LEN));
+    assert!(context.banks_client.get_account(transaction).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_closed_transaction_cannot_be_revived() {
+    let (mut context, program_id, multisig, transaction) = setup().await;
+    let payer = context.payer.pubkey();
+    let rent = context.banks_client.get_rent().await.unwrap();
+
+    // close, refund the account so it survives the transaction, and propose
+    // anew in it, all at once
+    let instructions = [
+        close_transaction(&program_id, &transaction, &payer, &payer).unwrap(),
+        system_instruction::transfer(
+            &payer,
+            &transaction,
+            rent.minimum_balance(state::Transaction::LEN),
+        ),
+        create_transaction(
+            &program_id,
+            &transaction,
+            &multisig,
+            &payer,
+            change_threshold_instructions(program_id, multisig, 1),
+            None,
+            None,
+        )
+        .unwrap(),
+    ];
+    let result = process(&mut context, &instructions, &[]).await;
+    assert_instruction_error(result, InstructionError::IncorrectProgramId);
+
+    // the refund alone leaves an empty system account, not a proposal
+    let instructions = &instructions[0..2];
+    process(&mut context, instructions, &[]).await.unwrap();
+    let account = context.banks_client.get_account(transaction).await.unwrap().unwrap();
+    assert_eq!(account.owner, system_program::id());
+    assert!(account.data.is_empty());
+}
@@ -0,0 +1,212 @@
+//! Helpers shared by the program tests
+#![allow(dead_code)]
+
+use solana_program::{
+    instruction::Instruction, program_pack::Pack, pubkey::Pubkey, system_instruction,
+    sysvar::clock::Clock,
+};
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    instruction::InstructionError,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+    transport::TransportError,
+};
+use General_Multisig::{
+    instruction::{allocate_pda, change_threshold, create_transaction, initialize_multisig},
+    processor::process_instruction,
+    state::{self, Multisig, TransactionInstruction},
+};
+
+pub const NONCE: u64 = 0;
+
+/// Starts a bank with the program loaded at a fresh id
+pub async fn start() -> (ProgramTestContext, Pubkey) {
+    let program_id = Pubkey::new_unique();
+    let context = ProgramTest::new(
+        "General_Multisig",
+        program_id,
+        processor!(process_instruction),
+    )
+    .start_with_context()
+    .await;
+    (context, program_id)
+}
+
+/// Sends `instructions` in one transaction paid for by the payer
+pub async fn process(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransportError> {
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.into())
+}
+
+/// Waits for a new blockhash, so resending the same instructions makes a
+/// new transaction rather than a duplicate
+pub async fn refresh_blockhash(context: &mut ProgramTestContext) {
+    // get_new_latest_blockhash waits for a hash other than last_blockhash,
+    // which `process` does not keep up to date
+    context.last_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    context.get_new_latest_blockhash().await.unwrap();
+}
+
+/// Creates the multisig of the payer for `NONCE` with the given owners
+pub async fn create_multisig(
+    context: &mut ProgramTestContext,
+    program_id: Pubkey,
+    owners: &[&Pubkey],
+    m: u16,
+    weights: &[u8],
+    roles: &[u8],
+) -> Pubkey {
+    create_multisig_with_nonce(context, program_id, NONCE, owners, m, weights, roles).await
+}
+
+/// Creates the multisig of the payer for `nonce` with the given owners
+pub async fn create_multisig_with_nonce(
+    context: &mut ProgramTestContext,
+    program_id: Pubkey,
+    nonce: u64,
+    owners: &[&Pubkey],
+    m: u16,
+    weights: &[u8],
+    roles: &[u8],
+) -> Pubkey {
+    let payer = context.payer.pubkey();
+    let (multisig, _) = Multisig::find_address(&program_id, &payer, nonce);
+
+    let instructions = [
+        allocate_pda(&program_id, &payer, nonce).unwrap(),
+        initialize_multisig(&program_id, &multisig, &payer, owners, m, nonce, weights, roles)
+            .unwrap(),
+    ];
+    process(context, &instructions, &[]).await.unwrap();
+
+    multisig
+}
+
+/// The instruction raising or lowering the threshold of `multisig` to `m`
+pub fn change_threshold_instructions(
+    program_id: Pubkey,
+    multisig: Pubkey,
+    m: u16,
+) -> Vec<TransactionInstruction> {
+    vec![change_threshold(&program_id, &multisig, m).unwrap().into()]
+}
+
+/// Instructions creating a proposal account and proposing `instructions` in
+/// it, valid between the `valid_after` and `expires_at` of `window`
+pub async fn propose_instructions(
+    context: &mut ProgramTestContext,
+    program_id: Pubkey,
+    multisig: Pubkey,
+    transaction: Pubkey,
+    proposer: Pubkey,
+    instructions: Vec<TransactionInstruction>,
+    (valid_after, expires_at): (Option<i64>, Option<i64>),
+) -> [Instruction; 2] {
+    let rent = context.banks_client.get_rent().await.unwrap();
+    [
+        system_instruction::create_account(
+            &context.payer.pubkey(),
+            &transaction,
+            rent.minimum_balance(state::Transaction::LEN),
+            state::Transaction::LEN as u64,
+            &program_id,
+        ),
+        create_transaction(
+            &program_id,
+            &transaction,
+            &multisig,
+            &proposer,
+            instructions,
+            valid_after,
+            expires_at,
+        )
+        .unwrap(),
+    ]
+}
+
+/// Proposes `instructions` as the payer and returns the proposal
+pub async fn propose(
+    context: &mut ProgramTestContext,
+    program_id: Pubkey,
+    multisig: Pubkey,
+    instructions: Vec<TransactionInstruction>,
+    valid_after: Option<i64>,
+    expires_at: Option<i64>,
+) -> Pubkey {
+    let payer = context.payer.pubkey();
+    let transaction = Keypair::new();
+    let instructions = propose_instructions(
+        context,
+        program_id,
+        multisig,
+        transaction.pubkey(),
+        payer,
+        instructions,
+        (valid_after, expires_at),
+    )
+    .await;
+    process(context, &instructions, &[&transaction]).await.unwrap();
+
+    transaction.pubkey()
+}
+
+pub async fn load_multisig(context: &mut ProgramTestContext, multisig: Pubkey) -> Multisig {
+    let account = context
+        .banks_client
+        .get_account(multisig)
+        .await
+        .unwrap()
+        .unwrap();
+    Multisig::unpack(&account.data).unwrap()
+}
+
+pub async fn load_transaction(
+    context: &mut ProgramTestContext,
+    transaction: Pubkey,
+) -> state::Transaction {
+    let account = context
+        .banks_client
+        .get_account(transaction)
+        .await
+        .unwrap()
+        .unwrap();
+    state::Transaction::unpack(&account.data).unwrap()
+}
+
+pub async fn threshold(context: &mut ProgramTestContext, multisig: Pubkey) -> u16 {
+    load_multisig(context, multisig).await.m
+}
+
+/// Moves the bank forward and sets the clock to `unix_timestamp`
+pub async fn warp_to_timestamp(context: &mut ProgramTestContext, unix_timestamp: i64) {
+    let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    context.warp_to_slot(clock.slot + 100).unwrap();
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = unix_timestamp;
+    context.set_sysvar(&clock);
+}
+
+pub fn assert_instruction_error(result: Result<(), TransportError>, expected: InstructionError) {
+    match result.unwrap_err() {
+        TransportError::TransactionError(TransactionError::InstructionError(_, err))
+            if err == expected => {}
+        err => panic!("unexpected error {:?}", err),
+    }
+}
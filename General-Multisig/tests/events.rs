@@ -0,0 +1,86 @@
+//! `sol_log_data` only reaches the transaction logs when the program runs as
+//! BPF, so these tests feed the decoder logs in the runtime's format.
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::BorshSerialize;
+use solana_program::pubkey::Pubkey;
+use General_Multisig::event::{
+    parse_logs, Approved, Executed, MultisigCreated, MultisigEvent, ProposalCreated,
+};
+
+fn program_data(event: &MultisigEvent) -> String {
+    format!("Program data: {}", STANDARD.encode(event.try_to_vec().unwrap()))
+}
+
+#[test]
+fn test_parse_proposal_lifecycle() {
+    let program_id = Pubkey::new_unique();
+    let multisig = Pubkey::new_unique();
+    let transaction = Pubkey::new_unique();
+    let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let events = vec![
+        MultisigEvent::MultisigCreated(MultisigCreated {
+            multisig,
+            creator: alice,
+            m: 2,
+            owners: vec![alice, bob],
+        }),
+        MultisigEvent::ProposalCreated(ProposalCreated {
+            multisig,
+            transaction,
+            proposer: alice,
+        }),
+        MultisigEvent::Approved(Approved {
+            multisig,
+            transaction,
+            owner: alice,
+        }),
+        MultisigEvent::Approved(Approved {
+            multisig,
+            transaction,
+            owner: bob,
+        }),
+        MultisigEvent::Executed(Executed {
+            multisig,
+            transaction,
+            executor: bob,
+        }),
+    ];
+
+    let mut logs = Vec::new();
+    for event in events.iter() {
+        logs.push(format!("Program {} invoke [1]", program_id));
+        logs.push("Program log: Instruction: Approve".to_string());
+        logs.push(program_data(event));
+        logs.push(format!("Program {} consumed 1000 of 200000 compute units", program_id));
+        logs.push(format!("Program {} success", program_id));
+    }
+
+    assert_eq!(parse_logs(&program_id, &logs), events);
+}
+
+#[test]
+fn test_parse_logs_ignores_other_programs() {
+    let program_id = Pubkey::new_unique();
+    let other_program_id = Pubkey::new_unique();
+    let event = MultisigEvent::Executed(Executed {
+        multisig: Pubkey::new_unique(),
+        transaction: Pubkey::new_unique(),
+        executor: Pubkey::new_unique(),
+    });
+    let logs = vec![
+        format!("Program {} invoke [1]", other_program_id),
+        program_data(&event),
+        format!("Program {} success", other_program_id),
+        format!("Program {} invoke [1]", program_id),
+        "Program log: Instruction: ExecuteTransaction".to_string(),
+        format!("Program {} invoke [2]", other_program_id),
+        program_data(&event),
+        format!("Program {} failed: custom program error: 0x0", other_program_id),
+        program_data(&event),
+        format!("Program data: {}", STANDARD.encode([255])),
+        format!("Program {} success", program_id),
+        program_data(&event),
+    ];
+
+    assert_eq!(parse_logs(&program_id, &logs), vec![event]);
+}
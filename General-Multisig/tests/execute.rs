@@ -0,0 +1,161 @@
+mod common;
+
+use common::*;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey, system_instruction};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    instruction::InstructionError,
+    signature::{Keypair, Signer},
+};
+use General_Multisig::{
+    instruction::{approve, execute_transaction},
+    state::{Multisig, TransactionInstruction, TransactionStatus, ROLE_ALL},
+};
+
+const PAYOUT: u64 = 1_000_000;
+
+/// Creates a multisig owned by the payer and a second key, with a threshold
+/// of one, and funds its vault
+async fn setup() -> (ProgramTestContext, Pubkey, Pubkey) {
+    let (mut context, program_id) = start().await;
+    let payer = context.payer.pubkey();
+    let multisig = create_multisig(
+        &mut context,
+        program_id,
+        &[&payer, &Pubkey::new_unique()],
+        1,
+        &[1, 1],
+        &[ROLE_ALL, ROLE_ALL],
+    )
+    .await;
+    let (vault, _) = Multisig::find_vault_address(&program_id, &multisig);
+    let instructions = [system_instruction::transfer(&payer, &vault, 10 * PAYOUT)];
+    process(&mut context, &instructions, &[]).await.unwrap();
+
+    (context, program_id, multisig)
+}
+
+/// Instructions paying `PAYOUT` from the vault of `multisig` to `recipient`
+fn payout_instructions(
+    program_id: Pubkey,
+    multisig: Pubkey,
+    recipient: Pubkey,
+) -> Vec<TransactionInstruction> {
+    let (vault, _) = Multisig::find_vault_address(&program_id, &multisig);
+    vec![system_instruction::transfer(&vault, &recipient, PAYOUT).into()]
+}
+
+#[tokio::test]
+async fn test_execute_only_once() {
+    let (mut context, program_id, multisig) = setup().await;
+    let payer = context.payer.pubkey();
+    let recipient = Pubkey::new_unique();
+    let instructions = payout_instructions(program_id, multisig, recipient);
+    let transaction = propose(&mut context, program_id, multisig, instructions.clone(), None, None).await;
+
+    let execute =
+        [execute_transaction(&program_id, &transaction, &multisig, &payer, &instructions).unwrap()];
+    process(&mut context, &execute, &[]).await.unwrap();
+    assert_eq!(context.banks_client.get_balance(recipient).await.unwrap(), PAYOUT);
+
+    refresh_blockhash(&mut context).await;
+    let result = process(&mut context, &execute, &[]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    assert_eq!(context.banks_client.get_balance(recipient).await.unwrap(), PAYOUT);
+}
+
+#[tokio::test]
+async fn test_execute_only_once_in_one_transaction() {
+    let (mut context, program_id, multisig) = setup().await;
+    let payer = context.payer.pubkey();
+    let recipient = Pubkey::new_unique();
+    let instructions = payout_instructions(program_id, multisig, recipient);
+    let transaction = propose(&mut context, program_id, multisig, instructions.clone(), None, None).await;
+
+    let execute =
+        execute_transaction(&program_id, &transaction, &multisig, &payer, &instructions).unwrap();
+    let result = process(&mut context, &[execute.clone(), execute], &[]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    assert_eq!(context.banks_client.get_balance(recipient).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_reject_other_multisig() {
+    let (mut context, program_id, multisig) = setup().await;
+    let payer = context.payer.pubkey();
+    let other_owner = Keypair::new();
+    let other = create_multisig_with_nonce(
+        &mut context,
+        program_id,
+        NONCE + 1,
+        &[&payer, &other_owner.pubkey()],
+        1,
+        &[1, 1],
+        &[ROLE_ALL, ROLE_ALL],
+    )
+    .await;
+    let recipient = Pubkey::new_unique();
+    let instructions = payout_instructions(program_id, multisig, recipient);
+    let transaction = propose(&mut context, program_id, multisig, instructions.clone(), None, None).await;
+
+    // a proposal of one multisig can neither be voted on nor executed through another
+    let result = process(
+        &mut context,
+        &[approve(&program_id, &transaction, &other, &other_owner.pubkey()).unwrap()],
+        &[&other_owner],
+    )
+    .await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+
+    let mut execute =
+        execute_transaction(&program_id, &transaction, &other, &payer, &instructions).unwrap();
+    // the recorded vault is not the one of `other`, so leave its signature to the program
+    for account in execute.accounts.iter_mut().skip(3) {
+        account.is_signer = false;
+    }
+    let result = process(&mut context, &[execute], &[]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    assert_eq!(context.banks_client.get_balance(recipient).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_failing_instruction_rolls_back_execution() {
+    let (mut context, program_id, multisig) = setup().await;
+    let payer = context.payer.pubkey();
+    let recipient = Pubkey::new_unique();
+    let (vault, _) = Multisig::find_vault_address(&program_id, &multisig);
+    // the first payout succeeds on its own, the second exceeds the vault
+    let mut instructions = payout_instructions(program_id, multisig, recipient);
+    instructions.push(system_instruction::transfer(&vault, &recipient, 100 * PAYOUT).into());
+    let transaction = propose(&mut context, program_id, multisig, instructions.clone(), None, None).await;
+
+    let execute =
+        [execute_transaction(&program_id, &transaction, &multisig, &payer, &instructions).unwrap()];
+    let result = process(&mut context, &execute, &[]).await;
+    assert_instruction_error(result, InstructionError::Custom(1));
+    assert_eq!(context.banks_client.get_balance(recipient).await.unwrap(), 0);
+    assert_eq!(
+        load_transaction(&mut context, transaction).await.status,
+        TransactionStatus::Active
+    );
+}
+
+#[tokio::test]
+async fn test_reject_extra_account() {
+    let (mut context, program_id, multisig) = setup().await;
+    let payer = context.payer.pubkey();
+    let recipient = Pubkey::new_unique();
+    let instructions = payout_instructions(program_id, multisig, recipient);
+    let transaction = propose(&mut context, program_id, multisig, instructions.clone(), None, None).await;
+
+    let mut execute =
+        execute_transaction(&program_id, &transaction, &multisig, &payer, &instructions).unwrap();
+    execute.accounts.push(AccountMeta::new_readonly(Pubkey::new_unique(), false));
+    let result = process(&mut context, &[execute], &[]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    assert_eq!(context.banks_client.get_balance(recipient).await.unwrap(), 0);
+    assert_eq!(
+        load_transaction(&mut context, transaction).await.status,
+        TransactionStatus::Active
+    );
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use common::*;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::{instruction::InstructionError, signature::Signer};
+use General_Multisig::{
+    instruction::{allocate_pda, initialize_multisig},
+    state::{Multisig, ROLE_ALL},
+};
+
+/// Tries to initialize the multisig of the payer with `owners`
+async fn initialize(owners: &[&Pubkey]) -> Result<(), InstructionError> {
+    let (mut context, program_id) = start().await;
+    let payer = context.payer.pubkey();
+    let (multisig, _) = Multisig::find_address(&program_id, &payer, NONCE);
+    let weights = vec![1; owners.len()];
+    let roles = vec![ROLE_ALL; owners.len()];
+
+    let instructions = [
+        allocate_pda(&program_id, &payer, NONCE).unwrap(),
+        initialize_multisig(&program_id, &multisig, &payer, owners, 1, NONCE, &weights, &roles)
+            .unwrap(),
+    ];
+    let result = process(&mut context, &instructions, &[]).await;
+    if result.is_ok() {
+        return Ok(());
+    }
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    Err(InstructionError::InvalidArgument)
+}
+
+#[tokio::test]
+async fn test_initialize_distinct_owners() {
+    initialize(&[&Pubkey::new_unique(), &Pubkey::new_unique()]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_reject_duplicate_owner() {
+    let owner = Pubkey::new_unique();
+    initialize(&[&owner, &Pubkey::new_unique(), &owner]).await.unwrap_err();
+}
+
+#[tokio::test]
+async fn test_reject_zero_owner() {
+    initialize(&[&Pubkey::new_unique(), &Pubkey::default()]).await.unwrap_err();
+}
@@ -0,0 +1,183 @@
+use solana_program::{program_error::ProgramError, pubkey::Pubkey, system_instruction, system_program};
+use General_Multisig::{
+    instruction::{
+        approve, change_threshold, create_transaction, execute_transaction, initialize_multisig,
+        MultisigInstruction,
+    },
+    state::{Multisig, TransactionAccount, TransactionInstruction, ROLE_ALL, ROLE_APPROVE},
+};
+
+fn check_round_trip(instruction: MultisigInstruction, expected: &[u8]) {
+    let packed = instruction.pack();
+    assert_eq!(packed, expected);
+    assert_eq!(MultisigInstruction::unpack(&packed).unwrap(), instruction);
+}
+
+#[test]
+fn test_pack_unpack_without_fields() {
+    check_round_trip(MultisigInstruction::Approve, &[3]);
+    check_round_trip(MultisigInstruction::ExecuteTransaction, &[4]);
+    check_round_trip(MultisigInstruction::Reject, &[7]);
+    check_round_trip(MultisigInstruction::Cancel, &[8]);
+    check_round_trip(MultisigInstruction::CloseTransaction, &[9]);
+    check_round_trip(MultisigInstruction::Unapprove, &[10]);
+    check_round_trip(MultisigInstruction::ApproveWithSignatures, &[11]);
+}
+
+#[test]
+fn test_pack_unpack_allocate_and_initialize() {
+    let mut expected = vec![0];
+    expected.extend_from_slice(&7u64.to_le_bytes());
+    check_round_trip(MultisigInstruction::AllocatePDA { nonce: 7 }, &expected);
+
+    let mut expected = vec![1];
+    expected.extend_from_slice(&3u16.to_le_bytes());
+    expected.extend_from_slice(&7u64.to_le_bytes());
+    expected.extend_from_slice(&[2, ROLE_ALL, 1, ROLE_APPROVE]);
+    check_round_trip(
+        MultisigInstruction::InitializeMultisig {
+            m: 3,
+            nonce: 7,
+            weights: vec![2, 1],
+            roles: vec![ROLE_ALL, ROLE_APPROVE],
+        },
+        &expected,
+    );
+}
+
+#[test]
+fn test_pack_unpack_create_transaction() {
+    let program_id = Pubkey::new_unique();
+    let account = Pubkey::new_unique();
+    let instruction = MultisigInstruction::CreateTransaction {
+        instructions: vec![TransactionInstruction {
+            program_id,
+            accounts: vec![TransactionAccount {
+                pubkey: account,
+                is_signer: true,
+                is_writable: false,
+            }],
+            data: vec![6, 2, 0],
+        }],
+        valid_after: Some(-1),
+        expires_at: None,
+    };
+
+    let mut expected = vec![2];
+    expected.extend_from_slice(&1u16.to_le_bytes());
+    expected.extend_from_slice(program_id.as_ref());
+    expected.extend_from_slice(&1u16.to_le_bytes());
+    expected.extend_from_slice(account.as_ref());
+    expected.extend_from_slice(&[1, 0]);
+    expected.extend_from_slice(&3u16.to_le_bytes());
+    expected.extend_from_slice(&[6, 2, 0]);
+    expected.push(1);
+    expected.extend_from_slice(&(-1i64).to_le_bytes());
+    expected.extend_from_slice(&[0; 9]);
+    check_round_trip(instruction, &expected);
+}
+
+#[test]
+fn test_pack_unpack_owner_changes() {
+    let owner = Pubkey::new_unique();
+    let mut expected = vec![5, 1];
+    expected.extend_from_slice(owner.as_ref());
+    expected.extend_from_slice(&[2, ROLE_ALL]);
+    check_round_trip(
+        MultisigInstruction::SetOwners {
+            owners: vec![owner],
+            weights: vec![2],
+            roles: vec![ROLE_ALL],
+        },
+        &expected,
+    );
+
+    let mut expected = vec![6];
+    expected.extend_from_slice(&300u16.to_le_bytes());
+    check_round_trip(MultisigInstruction::ChangeThreshold { m: 300 }, &expected);
+}
+
+#[test]
+#[should_panic(expected = "one weight and role per owner")]
+fn test_pack_owners_without_weights() {
+    MultisigInstruction::SetOwners {
+        owners: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+        weights: vec![1],
+        roles: vec![ROLE_ALL],
+    }
+    .pack();
+}
+
+#[test]
+#[should_panic(expected = "one role per weight")]
+fn test_pack_weights_without_roles() {
+    MultisigInstruction::InitializeMultisig {
+        m: 1,
+        nonce: 0,
+        weights: vec![1, 1],
+        roles: vec![ROLE_ALL],
+    }
+    .pack();
+}
+
+#[test]
+fn test_builders() {
+    let program_id = Pubkey::new_unique();
+    let creator = Pubkey::new_unique();
+    let (multisig, _) = Multisig::find_address(&program_id, &creator, 0);
+    let (vault, _) = Multisig::find_vault_address(&program_id, &multisig);
+    let transaction = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+
+    let instruction = initialize_multisig(
+        &program_id,
+        &multisig,
+        &creator,
+        &[&creator, &owner],
+        2,
+        0,
+        &[1, 1],
+        &[ROLE_ALL, ROLE_ALL],
+    )
+    .unwrap();
+    assert_eq!(instruction.accounts.len(), 4);
+    assert!(instruction.accounts[1].is_signer);
+    assert_eq!(
+        initialize_multisig(&program_id, &multisig, &creator, &[&creator], 1, 0, &[1, 1], &[ROLE_ALL]),
+        Err(ProgramError::InvalidArgument)
+    );
+
+    let instruction = approve(&program_id, &transaction, &multisig, &owner).unwrap();
+    assert_eq!(MultisigInstruction::unpack(&instruction.data).unwrap(), MultisigInstruction::Approve);
+    assert!(instruction.accounts[2].is_signer);
+
+    // the multisig and vault signatures are provided by the program
+    let instructions: Vec<TransactionInstruction> = vec![
+        change_threshold(&program_id, &multisig, 1).unwrap().into(),
+        system_instruction::transfer(&vault, &owner, 1).into(),
+    ];
+    let instruction = create_transaction(
+        &program_id,
+        &transaction,
+        &multisig,
+        &creator,
+        instructions.clone(),
+        None,
+        None,
+    )
+    .unwrap();
+    match MultisigInstruction::unpack(&instruction.data).unwrap() {
+        MultisigInstruction::CreateTransaction { instructions: unpacked, .. } => {
+            assert_eq!(unpacked, instructions)
+        }
+        _ => panic!("unexpected instruction"),
+    }
+    let instruction =
+        execute_transaction(&program_id, &transaction, &multisig, &creator, &instructions).unwrap();
+    let keys: Vec<_> = instruction.accounts.iter().map(|meta| meta.pubkey).collect();
+    assert_eq!(
+        keys,
+        vec![transaction, multisig, creator, program_id, multisig, system_program::id(), vault, owner]
+    );
+    assert!(instruction.accounts[3..].iter().all(|meta| !meta.is_signer));
+}
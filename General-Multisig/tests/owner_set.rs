@@ -0,0 +1,169 @@
+mod common;
+
+use common::*;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    instruction::InstructionError,
+    signature::{Keypair, Signer},
+};
+use General_Multisig::{
+    instruction::{approve, change_threshold, execute_transaction, set_owners},
+    state::{TransactionInstruction, TransactionStatus, ROLE_ALL},
+};
+
+/// Creates a two of two multisig owned by the payer and `alice`
+async fn setup(alice: &Keypair) -> (ProgramTestContext, Pubkey, Pubkey) {
+    let (mut context, program_id) = start().await;
+    let payer = context.payer.pubkey();
+    let multisig = create_multisig(
+        &mut context,
+        program_id,
+        &[&payer, &alice.pubkey()],
+        2,
+        &[1, 1],
+        &[ROLE_ALL, ROLE_ALL],
+    )
+    .await;
+
+    (context, program_id, multisig)
+}
+
+/// Replaces the owners with the payer, `alice` and a new key through a
+/// proposal approved by both
+async fn change_owners(
+    context: &mut ProgramTestContext,
+    program_id: Pubkey,
+    multisig: Pubkey,
+    alice: &Keypair,
+) {
+    let payer = context.payer.pubkey();
+    let owners = [&payer, &alice.pubkey(), &Pubkey::new_unique()];
+    let instructions: Vec<TransactionInstruction> = vec![set_owners(
+        &program_id,
+        &multisig,
+        &owners,
+        &[1, 1, 1],
+        &[ROLE_ALL, ROLE_ALL, ROLE_ALL],
+    )
+    .unwrap()
+    .into()];
+    let transaction = propose(context, program_id, multisig, instructions.clone(), None, None).await;
+    let approve_and_execute = [
+        approve(&program_id, &transaction, &multisig, &alice.pubkey()).unwrap(),
+        execute_transaction(&program_id, &transaction, &multisig, &payer, &instructions).unwrap(),
+    ];
+    process(context, &approve_and_execute, &[alice]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_owner_change_bumps_seqno() {
+    let alice = Keypair::new();
+    let (mut context, program_id, multisig) = setup(&alice).await;
+
+    assert_eq!(load_multisig(&mut context, multisig).await.owner_set_seqno, 0);
+    change_owners(&mut context, program_id, multisig, &alice).await;
+    let owners = load_multisig(&mut context, multisig).await;
+    assert_eq!(owners.owner_set_seqno, 1);
+    assert_eq!(owners.n, 3);
+}
+
+#[tokio::test]
+async fn test_reject_approve_after_owner_change() {
+    let alice = Keypair::new();
+    let (mut context, program_id, multisig) = setup(&alice).await;
+    let transaction = propose(
+        &mut context,
+        program_id,
+        multisig,
+        change_threshold_instructions(program_id, multisig, 1),
+        None,
+        None,
+    )
+    .await;
+
+    change_owners(&mut context, program_id, multisig, &alice).await;
+
+    let instructions = [approve(&program_id, &transaction, &multisig, &alice.pubkey()).unwrap()];
+    let result = process(&mut context, &instructions, &[&alice]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_reject_execute_after_owner_change() {
+    let alice = Keypair::new();
+    let (mut context, program_id, multisig) = setup(&alice).await;
+    let payer = context.payer.pubkey();
+    let instructions = change_threshold_instructions(program_id, multisig, 1);
+    let transaction =
+        propose(&mut context, program_id, multisig, instructions.clone(), None, None).await;
+    let approval = [approve(&program_id, &transaction, &multisig, &alice.pubkey()).unwrap()];
+    process(&mut context, &approval, &[&alice]).await.unwrap();
+
+    // fully approved by the old owners, but no longer executable
+    change_owners(&mut context, program_id, multisig, &alice).await;
+
+    let execute =
+        execute_transaction(&program_id, &transaction, &multisig, &payer, &instructions).unwrap();
+    let result = process(&mut context, &[execute], &[]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    assert_eq!(threshold(&mut context, multisig).await, 2);
+}
+
+#[tokio::test]
+async fn test_reject_owner_change_without_multisig_signature() {
+    let alice = Keypair::new();
+    let (mut context, program_id, multisig) = setup(&alice).await;
+    let payer = context.payer.pubkey();
+
+    // called directly rather than executed, so the multisig cannot sign
+    let owners = [&payer, &Pubkey::new_unique()];
+    let instructions = [
+        set_owners(&program_id, &multisig, &owners, &[1, 1], &[ROLE_ALL, ROLE_ALL]).unwrap(),
+        change_threshold(&program_id, &multisig, 1).unwrap(),
+    ];
+    for mut instruction in instructions {
+        instruction.accounts[0].is_signer = false;
+        let result = process(&mut context, &[instruction], &[]).await;
+        assert_instruction_error(result, InstructionError::MissingRequiredSignature);
+    }
+
+    let unchanged = load_multisig(&mut context, multisig).await;
+    assert_eq!(unchanged.signers[1], alice.pubkey());
+    assert_eq!(unchanged.m, 2);
+    assert_eq!(unchanged.owner_set_seqno, 0);
+}
+
+#[tokio::test]
+async fn test_reject_duplicate_owner_proposal() {
+    let alice = Keypair::new();
+    let (mut context, program_id, multisig) = setup(&alice).await;
+    let payer = context.payer.pubkey();
+    let owners = [&payer, &alice.pubkey(), &payer];
+    let instructions: Vec<TransactionInstruction> = vec![set_owners(
+        &program_id,
+        &multisig,
+        &owners,
+        &[1, 1, 1],
+        &[ROLE_ALL, ROLE_ALL, ROLE_ALL],
+    )
+    .unwrap()
+    .into()];
+    let transaction =
+        propose(&mut context, program_id, multisig, instructions.clone(), None, None).await;
+
+    let approve_and_execute = [
+        approve(&program_id, &transaction, &multisig, &alice.pubkey()).unwrap(),
+        execute_transaction(&program_id, &transaction, &multisig, &payer, &instructions).unwrap(),
+    ];
+    let result = process(&mut context, &approve_and_execute, &[&alice]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+
+    let unchanged = load_multisig(&mut context, multisig).await;
+    assert_eq!(unchanged.n, 2);
+    assert_eq!(unchanged.owner_set_seqno, 0);
+    assert_eq!(
+        load_transaction(&mut context, transaction).await.status,
+        TransactionStatus::Active
+    );
+}
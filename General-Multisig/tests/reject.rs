@@ -0,0 +1,79 @@
+mod common;
+
+use common::*;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    instruction::InstructionError,
+    signature::{Keypair, Signer},
+};
+use General_Multisig::{
+    instruction::{approve, cancel, execute_transaction, reject},
+    state::{TransactionStatus, ROLE_ALL, ROLE_APPROVE, ROLE_EXECUTE, ROLE_PROPOSE},
+};
+
+/// Creates a two of three multisig of the payer, `alice` and `bob`, where
+/// only `bob` may not cancel, and proposes to lower its threshold
+async fn setup(alice: &Keypair, bob: &Keypair) -> (ProgramTestContext, Pubkey, Pubkey, Pubkey) {
+    let (mut context, program_id) = start().await;
+    let payer = context.payer.pubkey();
+    let multisig = create_multisig(
+        &mut context,
+        program_id,
+        &[&payer, &alice.pubkey(), &bob.pubkey()],
+        2,
+        &[1, 1, 1],
+        &[ROLE_ALL, ROLE_ALL, ROLE_PROPOSE | ROLE_APPROVE | ROLE_EXECUTE],
+    )
+    .await;
+    let instructions = change_threshold_instructions(program_id, multisig, 1);
+    let transaction = propose(&mut context, program_id, multisig, instructions, None, None).await;
+
+    (context, program_id, multisig, transaction)
+}
+
+#[tokio::test]
+async fn test_rejected_once_threshold_unreachable() {
+    let (alice, bob) = (Keypair::new(), Keypair::new());
+    let (mut context, program_id, multisig, transaction) = setup(&alice, &bob).await;
+    let payer = context.payer.pubkey();
+
+    // the payer and bob could still approve it
+    let instructions = [reject(&program_id, &transaction, &multisig, &alice.pubkey()).unwrap()];
+    process(&mut context, &instructions, &[&alice]).await.unwrap();
+    let status = load_transaction(&mut context, transaction).await.status;
+    assert_eq!(status, TransactionStatus::Active);
+
+    let instructions = [reject(&program_id, &transaction, &multisig, &bob.pubkey()).unwrap()];
+    process(&mut context, &instructions, &[&bob]).await.unwrap();
+    let status = load_transaction(&mut context, transaction).await.status;
+    assert_eq!(status, TransactionStatus::Rejected);
+
+    let instructions = [approve(&program_id, &transaction, &multisig, &alice.pubkey()).unwrap()];
+    let result = process(&mut context, &instructions, &[&alice]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    let instructions = change_threshold_instructions(program_id, multisig, 1);
+    let execute =
+        execute_transaction(&program_id, &transaction, &multisig, &payer, &instructions).unwrap();
+    let result = process(&mut context, &[execute], &[]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    assert_eq!(threshold(&mut context, multisig).await, 2);
+}
+
+#[tokio::test]
+async fn test_only_proposer_or_canceller_cancels() {
+    let (alice, bob) = (Keypair::new(), Keypair::new());
+    let (mut context, program_id, multisig, transaction) = setup(&alice, &bob).await;
+
+    let instructions = [cancel(&program_id, &transaction, &multisig, &bob.pubkey()).unwrap()];
+    let result = process(&mut context, &instructions, &[&bob]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    let status = load_transaction(&mut context, transaction).await.status;
+    assert_eq!(status, TransactionStatus::Active);
+
+    // alice did not propose it, but holds the cancel role
+    let instructions = [cancel(&program_id, &transaction, &multisig, &alice.pubkey()).unwrap()];
+    process(&mut context, &instructions, &[&alice]).await.unwrap();
+    let status = load_transaction(&mut context, transaction).await.status;
+    assert_eq!(status, TransactionStatus::Cancelled);
+}
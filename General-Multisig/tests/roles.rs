@@ -0,0 +1,116 @@
+mod common;
+
+use common::*;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    instruction::InstructionError,
+    signature::{Keypair, Signer},
+    transport::TransportError,
+};
+use General_Multisig::{
+    instruction::{approve, execute_transaction},
+    state::{ROLE_ALL, ROLE_APPROVE, ROLE_EXECUTE, ROLE_PROPOSE},
+};
+
+/// Creates a one of two multisig of the payer, `operator`, who may only
+/// propose and execute, and `approver`, who may only approve
+async fn setup(operator: &Keypair, approver: &Keypair) -> (ProgramTestContext, Pubkey, Pubkey) {
+    let (mut context, program_id) = start().await;
+    let payer = context.payer.pubkey();
+    let multisig = create_multisig(
+        &mut context,
+        program_id,
+        &[&payer, &operator.pubkey(), &approver.pubkey()],
+        1,
+        &[1, 1, 1],
+        &[ROLE_ALL, ROLE_PROPOSE | ROLE_EXECUTE, ROLE_APPROVE],
+    )
+    .await;
+
+    (context, program_id, multisig)
+}
+
+/// Proposes to raise the threshold of `multisig` to two as `proposer`
+async fn propose_as(
+    context: &mut ProgramTestContext,
+    program_id: Pubkey,
+    multisig: Pubkey,
+    proposer: &Keypair,
+) -> Result<Pubkey, TransportError> {
+    let transaction = Keypair::new();
+    let instructions = propose_instructions(
+        context,
+        program_id,
+        multisig,
+        transaction.pubkey(),
+        proposer.pubkey(),
+        change_threshold_instructions(program_id, multisig, 2),
+        (None, None),
+    )
+    .await;
+    process(context, &instructions, &[&transaction, proposer]).await?;
+
+    Ok(transaction.pubkey())
+}
+
+#[tokio::test]
+async fn test_operator_proposes_and_executes() {
+    let (operator, approver) = (Keypair::new(), Keypair::new());
+    let (mut context, program_id, multisig) = setup(&operator, &approver).await;
+    let instructions = change_threshold_instructions(program_id, multisig, 2);
+
+    // proposing does not count as an approval without the approve role
+    let transaction = propose_as(&mut context, program_id, multisig, &operator).await.unwrap();
+    assert_eq!(load_transaction(&mut context, transaction).await.signers[..3], [false; 3]);
+    let execute = [
+        execute_transaction(&program_id, &transaction, &multisig, &operator.pubkey(), &instructions)
+            .unwrap(),
+    ];
+    let result = process(&mut context, &execute, &[&operator]).await;
+    assert_instruction_error(result, InstructionError::MissingRequiredSignature);
+
+    let instructions = [approve(&program_id, &transaction, &multisig, &approver.pubkey()).unwrap()];
+    process(&mut context, &instructions, &[&approver]).await.unwrap();
+    refresh_blockhash(&mut context).await;
+    process(&mut context, &execute, &[&operator]).await.unwrap();
+    assert_eq!(threshold(&mut context, multisig).await, 2);
+}
+
+#[tokio::test]
+async fn test_reject_propose_without_role() {
+    let (operator, approver) = (Keypair::new(), Keypair::new());
+    let (mut context, program_id, multisig) = setup(&operator, &approver).await;
+
+    let result = propose_as(&mut context, program_id, multisig, &approver).await;
+    assert_instruction_error(result.map(|_| ()), InstructionError::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_reject_approve_without_role() {
+    let (operator, approver) = (Keypair::new(), Keypair::new());
+    let (mut context, program_id, multisig) = setup(&operator, &approver).await;
+    let transaction = propose_as(&mut context, program_id, multisig, &operator).await.unwrap();
+
+    let instructions = [approve(&program_id, &transaction, &multisig, &operator.pubkey()).unwrap()];
+    let result = process(&mut context, &instructions, &[&operator]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    assert!(!load_transaction(&mut context, transaction).await.signers[1]);
+}
+
+#[tokio::test]
+async fn test_reject_execute_without_role() {
+    let (operator, approver) = (Keypair::new(), Keypair::new());
+    let (mut context, program_id, multisig) = setup(&operator, &approver).await;
+    let instructions = change_threshold_instructions(program_id, multisig, 2);
+    let transaction = propose_as(&mut context, program_id, multisig, &operator).await.unwrap();
+
+    let approve_and_execute = [
+        approve(&program_id, &transaction, &multisig, &approver.pubkey()).unwrap(),
+        execute_transaction(&program_id, &transaction, &multisig, &approver.pubkey(), &instructions)
+            .unwrap(),
+    ];
+    let result = process(&mut context, &approve_and_execute, &[&approver]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    assert_eq!(threshold(&mut context, multisig).await, 1);
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use common::*;
+use solana_program::{instruction::Instruction, pubkey::Pubkey, sysvar::clock::Clock};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    instruction::InstructionError,
+    signature::{Keypair, Signer},
+};
+use General_Multisig::{instruction::execute_transaction, state::ROLE_ALL};
+
+/// Creates a multisig owned by the payer and a second key of weight one each, with a threshold of one
+async fn setup() -> (ProgramTestContext, Pubkey, Pubkey) {
+    let (mut context, program_id) = start().await;
+    let payer = context.payer.pubkey();
+    let multisig = create_multisig(
+        &mut context,
+        program_id,
+        &[&payer, &Pubkey::new_unique()],
+        1,
+        &[1, 1],
+        &[ROLE_ALL, ROLE_ALL],
+    )
+    .await;
+
+    (context, program_id, multisig)
+}
+
+/// Proposes raising the threshold to two, approved by the payer as proposer
+async fn propose_change_threshold(
+    context: &mut ProgramTestContext,
+    program_id: Pubkey,
+    multisig: Pubkey,
+    valid_after: Option<i64>,
+    expires_at: Option<i64>,
+) -> Pubkey {
+    let instructions = change_threshold_instructions(program_id, multisig, 2);
+    propose(context, program_id, multisig, instructions, valid_after, expires_at).await
+}
+
+fn execute(program_id: Pubkey, multisig: Pubkey, transaction: Pubkey, executor: Pubkey) -> Instruction {
+    let instructions = change_threshold_instructions(program_id, multisig, 2);
+    execute_transaction(&program_id, &transaction, &multisig, &executor, &instructions).unwrap()
+}
+
+#[tokio::test]
+async fn test_execute_after_timelock() {
+    let (mut context, program_id, multisig) = setup().await;
+    let payer = context.payer.pubkey();
+    let now = context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+    let transaction =
+        propose_change_threshold(&mut context, program_id, multisig, Some(now + 3600), None).await;
+
+    let result = process(&mut context, &[execute(program_id, multisig, transaction, payer)], &[]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    assert_eq!(threshold(&mut context, multisig).await, 1);
+
+    warp_to_timestamp(&mut context, now + 3600).await;
+    process(&mut context, &[execute(program_id, multisig, transaction, payer)], &[])
+        .await
+        .unwrap();
+    assert_eq!(threshold(&mut context, multisig).await, 2);
+}
+
+#[tokio::test]
+async fn test_execute_before_expiry() {
+    let (mut context, program_id, multisig) = setup().await;
+    let payer = context.payer.pubkey();
+    let now = context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+    let transaction =
+        propose_change_threshold(&mut context, program_id, multisig, None, Some(now + 60)).await;
+
+    warp_to_timestamp(&mut context, now + 59).await;
+    process(&mut context, &[execute(program_id, multisig, transaction, payer)], &[])
+        .await
+        .unwrap();
+    assert_eq!(threshold(&mut context, multisig).await, 2);
+}
+
+#[tokio::test]
+async fn test_execute_after_expiry() {
+    let (mut context, program_id, multisig) = setup().await;
+    let payer = context.payer.pubkey();
+    let now = context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+    let transaction =
+        propose_change_threshold(&mut context, program_id, multisig, None, Some(now + 60)).await;
+
+    warp_to_timestamp(&mut context, now + 60).await;
+    let result = process(&mut context, &[execute(program_id, multisig, transaction, payer)], &[]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    assert_eq!(threshold(&mut context, multisig).await, 1);
+}
+
+#[tokio::test]
+async fn test_reject_inverted_window() {
+    let (mut context, program_id, multisig) = setup().await;
+    let payer = context.payer.pubkey();
+    let now = context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+    let transaction = Keypair::new();
+
+    let instructions = propose_instructions(
+        &mut context,
+        program_id,
+        multisig,
+        transaction.pubkey(),
+        payer,
+        Vec::new(),
+        (Some(now + 120), Some(now + 60)),
+    )
+    .await;
+    let result = process(&mut context, &instructions, &[&transaction]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::*;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    instruction::InstructionError,
+    signature::{Keypair, Signer},
+};
+use General_Multisig::{
+    instruction::{allocate_pda, approve, execute_transaction, initialize_multisig},
+    state::{Multisig, ROLE_ALL, ROLE_EXECUTE, ROLE_PROPOSE},
+};
+
+const WEIGHTS: [u8; 3] = [3, 1, 1];
+
+/// Creates a multisig where `heavy` and the two `light` owners weigh
+/// `WEIGHTS` against a threshold of three, and lets the payer, who cannot
+/// approve, propose to lower the threshold
+async fn setup(heavy: &Keypair, light: &[Keypair; 2]) -> (ProgramTestContext, Pubkey, Pubkey, Pubkey) {
+    let (mut context, program_id) = start().await;
+    let payer = context.payer.pubkey();
+    let multisig = create_multisig(
+        &mut context,
+        program_id,
+        &[&payer, &heavy.pubkey(), &light[0].pubkey(), &light[1].pubkey()],
+        3,
+        &[1, WEIGHTS[0], WEIGHTS[1], WEIGHTS[2]],
+        &[ROLE_PROPOSE | ROLE_EXECUTE, ROLE_ALL, ROLE_ALL, ROLE_ALL],
+    )
+    .await;
+    let instructions = change_threshold_instructions(program_id, multisig, 1);
+    let transaction = propose(&mut context, program_id, multisig, instructions, None, None).await;
+
+    (context, program_id, multisig, transaction)
+}
+
+#[tokio::test]
+async fn test_execute_with_heavy_approval() {
+    let (heavy, light) = (Keypair::new(), [Keypair::new(), Keypair::new()]);
+    let (mut context, program_id, multisig, transaction) = setup(&heavy, &light).await;
+    let payer = context.payer.pubkey();
+    let instructions = change_threshold_instructions(program_id, multisig, 1);
+
+    let approve_and_execute = [
+        approve(&program_id, &transaction, &multisig, &heavy.pubkey()).unwrap(),
+        execute_transaction(&program_id, &transaction, &multisig, &payer, &instructions).unwrap(),
+    ];
+    process(&mut context, &approve_and_execute, &[&heavy]).await.unwrap();
+    assert_eq!(threshold(&mut context, multisig).await, 1);
+}
+
+#[tokio::test]
+async fn test_reject_execute_with_light_approvals() {
+    let (heavy, light) = (Keypair::new(), [Keypair::new(), Keypair::new()]);
+    let (mut context, program_id, multisig, transaction) = setup(&heavy, &light).await;
+    let payer = context.payer.pubkey();
+    let instructions = change_threshold_instructions(program_id, multisig, 1);
+
+    // two of three owners, but only two of three weight
+    let approvals = [
+        approve(&program_id, &transaction, &multisig, &light[0].pubkey()).unwrap(),
+        approve(&program_id, &transaction, &multisig, &light[1].pubkey()).unwrap(),
+    ];
+    process(&mut context, &approvals, &[&light[0], &light[1]]).await.unwrap();
+    let execute =
+        execute_transaction(&program_id, &transaction, &multisig, &payer, &instructions).unwrap();
+    let result = process(&mut context, &[execute], &[]).await;
+    assert_instruction_error(result, InstructionError::MissingRequiredSignature);
+    assert_eq!(threshold(&mut context, multisig).await, 3);
+}
+
+#[tokio::test]
+async fn test_reject_threshold_out_of_weight() {
+    let (mut context, program_id) = start().await;
+    let payer = context.payer.pubkey();
+    let (multisig, _) = Multisig::find_address(&program_id, &payer, NONCE);
+    let owners = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+    let owners: Vec<&Pubkey> = owners.iter().collect();
+    let roles = [ROLE_ALL; 3];
+    let total_weight: u16 = WEIGHTS.iter().map(|weight| *weight as u16).sum();
+
+    for m in [0, total_weight + 1] {
+        let instructions = [
+            allocate_pda(&program_id, &payer, NONCE).unwrap(),
+            initialize_multisig(&program_id, &multisig, &payer, &owners, m, NONCE, &WEIGHTS, &roles)
+                .unwrap(),
+        ];
+        let result = process(&mut context, &instructions, &[]).await;
+        assert_instruction_error(result, InstructionError::InvalidArgument);
+    }
+    assert!(context.banks_client.get_account(multisig).await.unwrap().is_none());
+}
@@ -71,6 +71,11 @@ const PROGRAM_SO_PATH = path.join(PROGRAM_PATH, 'PrivilegeOwner_Attack.so');
  */
 const PROGRAM_KEYPAIR_PATH = path.join(PROGRAM_PATH, 'PrivilegeOwner_Attack-keypair.json');
 
+/**
+ * Size of the fake config account, matching the config of PrivilegeOwner
+ */
+const CONFIG_SIZE = 237;
+
 
 
 /**
@@ -148,7 +153,7 @@ export async function checkProgram(): Promise<void> {
     );
 
     const lamports = await connection.getMinimumBalanceForRentExemption(
-      1024,
+      CONFIG_SIZE,
     );
     
     console.log(
@@ -163,7 +168,7 @@ export async function checkProgram(): Promise<void> {
         seed: Config_SEED,
         newAccountPubkey: ConfigPubkey,
         lamports,
-        space: 1024,
+        space: CONFIG_SIZE,
         programId,
       }),
     );
@@ -11,7 +11,7 @@ use solana_program::{
     program_pack::{IsInitialized, Pack, Sealed},
 };
 
-/// Account
+/// Account, laid out like the config of PrivilegeOwner
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct Config {
     /// The admin of the config
@@ -24,6 +24,11 @@ pub struct Config {
     pub is_initialized: bool
 }
 
+/// Bytes of the PrivilegeOwner config after `is_initialized`: the pending
+/// admin, the emergency state and the guardians, left zeroed here
+const CONFIG_TAIL_LEN: usize = 203;
+
+
 
 impl Sealed for Config {}
 impl IsInitialized for Config {
@@ -33,11 +38,11 @@ impl IsInitialized for Config {
 }
 
 impl Pack for Config {
-    const LEN: usize = 1024;
+    const LEN: usize = 32 + 1 + 1 + CONFIG_TAIL_LEN;
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let src = array_ref![src, 0, 34];
-        let (admin, is_locked, is_initialized) =
-            array_refs![src, 32, 1, 1];
+        let src = array_ref![src, 0, Config::LEN];
+        let (admin, is_locked, is_initialized, _tail) =
+            array_refs![src, 32, 1, 1, CONFIG_TAIL_LEN];
         
         let is_locked = match is_locked {
             [0] => false,
@@ -58,9 +63,9 @@ impl Pack for Config {
         })
     }
     fn pack_into_slice(&self, dst: &mut [u8]) {
-        let dst = array_mut_ref![dst, 0, 34];
-        let (admin_dst, is_locked_dst, is_initialized_dst) = 
-             mut_array_refs![dst, 32, 1, 1];
+        let dst = array_mut_ref![dst, 0, Config::LEN];
+        let (admin_dst, is_locked_dst, is_initialized_dst, _tail_dst) = 
+             mut_array_refs![dst, 32, 1, 1, CONFIG_TAIL_LEN];
 
         let &Config {
             ref admin,
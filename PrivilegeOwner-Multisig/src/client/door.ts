@@ -87,6 +87,16 @@ const PROGRAM_SO_PATH = path.join(PROGRAM_PATH, 'PrivilegeOwner_Multisig.so');
  */
 const PROGRAM_KEYPAIR_PATH = path.join(PROGRAM_PATH, 'PrivilegeOwner_Multisig-keypair.json');
 
+/**
+ * Size of a door account
+ */
+const DOOR_SIZE = 35;
+
+/**
+ * Size of a multisig account
+ */
+const MULTISIG_SIZE = 355;
+
 
 
 /**
@@ -177,7 +187,7 @@ export async function checkProgram(): Promise<void> {
     );
 
     const lamports = await connection.getMinimumBalanceForRentExemption(
-      1024,
+      DOOR_SIZE,
     );
     
     console.log(
@@ -192,7 +202,7 @@ export async function checkProgram(): Promise<void> {
         seed: Door_SEED,
         newAccountPubkey: DoorPubkey,
         lamports,
-        space: 1024,
+        space: DOOR_SIZE,
         programId,
       }),
     );
@@ -219,7 +229,7 @@ export async function createMultisig(): Promise<void> {
     );
 
     const lamports = await connection.getMinimumBalanceForRentExemption(
-      1024,
+      MULTISIG_SIZE,
     );
     
     console.log(
@@ -234,7 +244,7 @@ export async function createMultisig(): Promise<void> {
         seed: Multisig_SEED,
         newAccountPubkey: MultisigPubkey,
         lamports,
-        space: 1024,
+        space: MULTISIG_SIZE,
         programId,
       }),
     );
@@ -10,14 +10,12 @@ use solana_program::{
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack, Sealed},
     system_instruction,
-    program::invoke_signed,
+    program::{invoke, invoke_signed},
+    sysvar::{rent::Rent, Sysvar},
 };
 use std::convert::TryInto;
 
 
-/// Size of PDA 
-pub const SIZE: usize = 1024;
-
 /// Instruction processor
 pub fn process_instruction(
     program_id: &Pubkey,
@@ -349,29 +347,71 @@ pub fn AllocatePDA(
         return Err(ProgramError::InvalidArgument);
     }
 
-    // Invoke the system program to allocate account data
-    invoke_signed(
-        &system_instruction::create_account(
-            &owner_info.key,
-            &allocated_info.key,
-            /// lamports
-            80179200,
-            SIZE as u64,
-            &program_id, 
-            ),
-        // Order doesn't matter and this slice could include all the accounts and be:
-        // `&accounts`
-        &[  owner_info.clone(),
-            system_program_info.clone(), // program being invoked also needs to be included
-            allocated_info.clone(),
-        ],
-
-        &[&[b"You pass butter", &[bump]]],
+    create_pda_account(
+        owner_info,
+        allocated_info,
+        system_program_info,
+        Config::LEN,
+        program_id,
+        &[b"You pass butter", &[bump]],
     )?;
 
     Ok(())
 }
 
+/// Creates the PDA `allocated_info` with `space` bytes owned by `owner`,
+/// funded by `payer_info` with exactly the rent-exempt minimum. If the
+/// address already holds lamports, which would make `create_account` fail,
+/// it is topped up, then allocated and assigned instead.
+pub fn create_pda_account<'a>(
+    payer_info: &AccountInfo<'a>,
+    allocated_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    space: usize,
+    owner: &Pubkey,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    let required_lamports = Rent::get()?.minimum_balance(space);
+    let current_lamports = allocated_info.lamports();
+    let account_infos = &[
+        payer_info.clone(),
+        system_program_info.clone(),
+        allocated_info.clone(),
+    ];
+
+    if current_lamports == 0 {
+        return invoke_signed(
+            &system_instruction::create_account(
+                payer_info.key,
+                allocated_info.key,
+                required_lamports,
+                space as u64,
+                owner,
+            ),
+            account_infos,
+            &[signer_seeds],
+        );
+    }
+
+    let top_up = required_lamports.saturating_sub(current_lamports);
+    if top_up > 0 {
+        invoke(
+            &system_instruction::transfer(payer_info.key, allocated_info.key, top_up),
+            account_infos,
+        )?;
+    }
+    invoke_signed(
+        &system_instruction::allocate(allocated_info.key, space as u64),
+        account_infos,
+        &[signer_seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(allocated_info.key, owner),
+        account_infos,
+        &[signer_seeds],
+    )
+}
+
 /// Initialize Multisig
 pub fn InitializeMultisig(
     accounts: &[AccountInfo],
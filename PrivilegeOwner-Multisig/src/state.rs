@@ -9,6 +9,14 @@ use solana_program::{
 };
 use crate::instruction::MAX_SIGNERS;
 
+/// First byte of a door account. Doors and configs are both owned by the
+/// program and the same size, so this tells them apart; a zero byte is an
+/// account not initialized yet
+pub const DOOR_ACCOUNT_TYPE: u8 = 1;
+
+/// First byte of a config account
+pub const CONFIG_ACCOUNT_TYPE: u8 = 2;
+
 /// Door 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct Door {
@@ -29,11 +37,16 @@ impl IsInitialized for Door {
 }
 
 impl Pack for Door {
-    const LEN: usize = 1024;
+    const LEN: usize = 35;
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let src = array_ref![src, 0, 34];
-        let (key, is_initialized, is_opened) =
-            array_refs![src, 32, 1, 1];
+        let src = array_ref![src, 0, Door::LEN];
+        let (account_type, key, is_initialized, is_opened) =
+            array_refs![src, 1, 32, 1, 1];
+        match account_type {
+            [0] | [DOOR_ACCOUNT_TYPE] => {}
+            _ => return Err(ProgramError::InvalidAccountData),
+        }
+
         let is_initialized = match is_initialized {
             [0] => false,
             [1] => true,
@@ -53,9 +66,9 @@ impl Pack for Door {
         })
     }
     fn pack_into_slice(&self, dst: &mut [u8]) {
-        let dst = array_mut_ref![dst, 0, 34];
-        let (key_dst, is_initialized_dst, is_opened_dst) = 
-             mut_array_refs![dst, 32, 1, 1];
+        let dst = array_mut_ref![dst, 0, Door::LEN];
+        let (account_type_dst, key_dst, is_initialized_dst, is_opened_dst) = 
+             mut_array_refs![dst, 1, 32, 1, 1];
 
         let &Door {
             ref key,
@@ -63,6 +76,7 @@ impl Pack for Door {
             is_opened,
         } = self;
 
+        account_type_dst[0] = DOOR_ACCOUNT_TYPE;
         key_dst.copy_from_slice(key.as_ref());
         is_initialized_dst[0] = is_initialized as u8;
         is_opened_dst[0] = is_opened as u8;
@@ -92,11 +106,15 @@ impl IsInitialized for Config {
 }
 
 impl Pack for Config {
-    const LEN: usize = 1024;
+    const LEN: usize = 35;
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let src = array_ref![src, 0, 34];
-        let (admin, is_locked, is_initialized) =
-            array_refs![src, 32, 1, 1];
+        let src = array_ref![src, 0, Config::LEN];
+        let (account_type, admin, is_locked, is_initialized) =
+            array_refs![src, 1, 32, 1, 1];
+        match account_type {
+            [0] | [CONFIG_ACCOUNT_TYPE] => {}
+            _ => return Err(ProgramError::InvalidAccountData),
+        }
         
         let is_locked = match is_locked {
             [0] => false,
@@ -117,9 +135,9 @@ impl Pack for Config {
         })
     }
     fn pack_into_slice(&self, dst: &mut [u8]) {
-        let dst = array_mut_ref![dst, 0, 34];
-        let (admin_dst, is_locked_dst, is_initialized_dst) = 
-             mut_array_refs![dst, 32, 1, 1];
+        let dst = array_mut_ref![dst, 0, Config::LEN];
+        let (account_type_dst, admin_dst, is_locked_dst, is_initialized_dst) = 
+             mut_array_refs![dst, 1, 32, 1, 1];
 
         let &Config {
             ref admin,
@@ -127,6 +145,7 @@ impl Pack for Config {
             is_initialized,
         } = self;
 
+        account_type_dst[0] = CONFIG_ACCOUNT_TYPE;
         admin_dst.copy_from_slice(admin.as_ref());
         is_locked_dst[0] = is_locked as u8;
         is_initialized_dst[0] = is_initialized as u8;
@@ -145,6 +164,7 @@ pub struct Multisig {
     /// Signer public keys
     pub signers: [Pubkey; MAX_SIGNERS],
 }
+
 impl Sealed for Multisig {}
 impl IsInitialized for Multisig {
     fn is_initialized(&self) -> bool {
@@ -153,9 +173,9 @@ impl IsInitialized for Multisig {
 }
 
 impl Pack for Multisig {
-    const LEN: usize = 1024;
+    const LEN: usize = 355;
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let src = array_ref![src, 0, 355];
+        let src = array_ref![src, 0, Multisig::LEN];
         #[allow(clippy::ptr_offset_with_cast)]
         let (m, n, is_initialized, signers_flat) = array_refs![src, 1, 1, 1, 32 * MAX_SIGNERS];
         let mut result = Multisig {
@@ -174,7 +194,7 @@ impl Pack for Multisig {
         Ok(result)
     }
     fn pack_into_slice(&self, dst: &mut [u8]) {
-        let dst = array_mut_ref![dst, 0, 355];
+        let dst = array_mut_ref![dst, 0, Multisig::LEN];
         #[allow(clippy::ptr_offset_with_cast)]
         let (m, n, is_initialized, signers_flat) = mut_array_refs![dst, 1, 1, 1, 32 * MAX_SIGNERS];
         *m = [self.m];
@@ -15,10 +15,13 @@ import {
 import {
   struct,
   u8,
+  u32,
+  ns64,
 } from '@solana/buffer-layout';
 
 import {
-  publicKey
+  publicKey,
+  u64,
 } from '@solana/buffer-layout-utils';
 
 import fs from 'mz/fs';
@@ -47,6 +50,11 @@ let programId: PublicKey;
  */
 let DoorPubkey: PublicKey; 
 
+/**
+ * Access log of the door
+ */
+let AccessLogPubkey: PublicKey;
+
 /**
  * Config's public key
  */
@@ -71,6 +79,11 @@ const PROGRAM_SO_PATH = path.join(PROGRAM_PATH, 'PrivilegeOwner.so');
  */
 const PROGRAM_KEYPAIR_PATH = path.join(PROGRAM_PATH, 'PrivilegeOwner-keypair.json');
 
+/**
+ * Index of the payer's door under the config
+ */
+const DOOR_INDEX = BigInt(0);
+
 
 
 /**
@@ -130,45 +143,18 @@ export async function checkProgram(): Promise<void> {
   }
   console.log(`Using program ${programId.toBase58()}`);
 
-  // Derive the address (public key) of a rectangle account from the program so that it's easy to find later.
-  const Door_SEED = 'door';
-  DoorPubkey = await PublicKey.createWithSeed(
-    payer.publicKey,
-    Door_SEED,
+  // Derive the address of the payer's door under the config, created by createDoor
+  let [ConfigPubkey] = await PublicKey.findProgramAddress([Buffer.from('You pass butter', 'utf8')], programId);
+  const index = Buffer.alloc(8);
+  index.writeBigUInt64LE(DOOR_INDEX);
+  [DoorPubkey] = await PublicKey.findProgramAddress(
+    [Buffer.from('door', 'utf8'), ConfigPubkey.toBuffer(), payer.publicKey.toBuffer(), index],
+    programId,
+  );
+  [AccessLogPubkey] = await PublicKey.findProgramAddress(
+    [Buffer.from('log', 'utf8'), DoorPubkey.toBuffer()],
     programId,
   );
-
-  // Check if the rectangle account has already been created
-  const DoorAccount = await connection.getAccountInfo(DoorPubkey);
-  if (DoorAccount === null) {
-    console.log(
-      'Creating account',
-      DoorPubkey.toBase58(),
-      'to store the data',
-    );
-
-    const lamports = await connection.getMinimumBalanceForRentExemption(
-      1024,
-    );
-    
-    console.log(
-      'lamports',
-      lamports
-    );
-
-    const transaction = new Transaction().add(
-      SystemProgram.createAccountWithSeed({
-        fromPubkey: payer.publicKey,
-        basePubkey: payer.publicKey,
-        seed: Door_SEED,
-        newAccountPubkey: DoorPubkey,
-        lamports,
-        space: 1024,
-        programId,
-      }),
-    );
-    await sendAndConfirmTransaction(connection, transaction, [payer]);
-  }
 }
 
 
@@ -227,33 +213,40 @@ export async function createConfig(): Promise<void> {
 }
 
 
-export interface InitializeDoorInstructionData {
+export interface CreateDoorInstructionData {
     instruction: number;
     key: PublicKey;
+    index: bigint;
 }
 
-export const initializeDoorInstructionData = struct<InitializeDoorInstructionData>([
+export const createDoorInstructionData = struct<CreateDoorInstructionData>([
     u8('instruction'),
     publicKey('key'),
+    u64('index'),
 ]);
 
 /**
- *  InitializeDoor  
+ *  Create the payer's door, signed and paid for by the admin of the config
  */
-export async function InitializeDoor(): Promise<void> {
-
-  const data = Buffer.alloc(initializeDoorInstructionData.span);
-  initializeDoorInstructionData.encode(
+export async function createDoor(): Promise<void> {
+  let [ConfigPubkey, bump] = await PublicKey.findProgramAddress([Buffer.from('You pass butter', 'utf8')], programId);
+  const data = Buffer.alloc(createDoorInstructionData.span);
+  createDoorInstructionData.encode(
       {
-        instruction: 0,
-        key: payer.publicKey,   
+        instruction: 7,
+        key: payer.publicKey,
+        index: DOOR_INDEX,
       },
       data
   );
-  
+
   const instruction = new TransactionInstruction({
     keys: [
+      {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
       {pubkey: DoorPubkey, isSigner: false, isWritable: true},
+      {pubkey: AccessLogPubkey, isSigner: false, isWritable: true},
+      {pubkey: ConfigPubkey, isSigner: false, isWritable: false},
+      {pubkey: payer.publicKey, isSigner: true, isWritable: true},
     ],
     programId,
     data: data, 
@@ -282,11 +275,11 @@ export const initializeConfigInstructionData = struct<InitializeConfigInstructio
 export async function InitializeConfig(): Promise<void> {
   let [ConfigPubkey, bump] = await PublicKey.findProgramAddress([Buffer.from('You pass butter', 'utf8')], programId);
   const data = Buffer.alloc(initializeConfigInstructionData.span);
-  const multisig = new PublicKey("G7AVMsy5HwsKq3Z2vQjStDavjKwFHY7fzrQG1Sdbq161");
+  // the payer administers the config, signing createDoor, lock and unlock
   initializeConfigInstructionData.encode(
       {
         instruction: 1,
-        key: multisig,   
+        key: payer.publicKey,   
       },
       data
   );
@@ -396,6 +389,254 @@ export async function unlock(): Promise<void> {
 
 
 
+export interface ProposeAdminInstructionData {
+    instruction: number;
+    key: PublicKey;
+}
+
+export const proposeAdminInstructionData = struct<ProposeAdminInstructionData>([
+    u8('instruction'),
+    publicKey('key'),
+]);
+
+/**
+ *  Propose a new admin, signed by the current admin (the payer)
+ */
+export async function proposeAdmin(newAdmin: PublicKey): Promise<void> {
+  let [ConfigPubkey, bump] = await PublicKey.findProgramAddress([Buffer.from('You pass butter', 'utf8')], programId);
+  const data = Buffer.alloc(proposeAdminInstructionData.span);
+  proposeAdminInstructionData.encode(
+      {
+        instruction: 8,
+        key: newAdmin,
+      },
+      data
+  );
+
+  const instruction = new TransactionInstruction({
+    keys: [
+      {pubkey: ConfigPubkey, isSigner: false, isWritable: true},
+      {pubkey: payer.publicKey, isSigner: true, isWritable: false},
+    ],
+    programId,
+    data: data, 
+  });
+
+  await sendAndConfirmTransaction(
+    connection,
+    new Transaction().add(instruction),
+    [payer],
+  );
+}
+
+
+export interface AdminTransferInstructionData {
+    instruction: number;
+}
+
+export const adminTransferInstructionData = struct<AdminTransferInstructionData>([
+    u8('instruction'),
+]);
+
+/**
+ *  Accept the admin role, signed by the pending admin
+ */
+export async function acceptAdmin(pendingAdmin: Keypair): Promise<void> {
+  let [ConfigPubkey, bump] = await PublicKey.findProgramAddress([Buffer.from('You pass butter', 'utf8')], programId);
+  const data = Buffer.alloc(adminTransferInstructionData.span);
+  adminTransferInstructionData.encode(
+      {
+        instruction: 9,
+      },
+      data
+  );
+
+  const instruction = new TransactionInstruction({
+    keys: [
+      {pubkey: ConfigPubkey, isSigner: false, isWritable: true},
+      {pubkey: pendingAdmin.publicKey, isSigner: true, isWritable: false},
+    ],
+    programId,
+    data: data, 
+  });
+
+  await sendAndConfirmTransaction(
+    connection,
+    new Transaction().add(instruction),
+    [payer, pendingAdmin],
+  );
+}
+
+/**
+ *  Cancel the pending admin transfer, signed by the current admin (the payer)
+ */
+export async function cancelAdminTransfer(): Promise<void> {
+  let [ConfigPubkey, bump] = await PublicKey.findProgramAddress([Buffer.from('You pass butter', 'utf8')], programId);
+  const data = Buffer.alloc(adminTransferInstructionData.span);
+  adminTransferInstructionData.encode(
+      {
+        instruction: 10,
+      },
+      data
+  );
+
+  const instruction = new TransactionInstruction({
+    keys: [
+      {pubkey: ConfigPubkey, isSigner: false, isWritable: true},
+      {pubkey: payer.publicKey, isSigner: true, isWritable: false},
+    ],
+    programId,
+    data: data, 
+  });
+
+  await sendAndConfirmTransaction(
+    connection,
+    new Transaction().add(instruction),
+    [payer],
+  );
+}
+
+
+
+export interface IssueGrantInstructionData {
+    instruction: number;
+    grantee: PublicKey;
+    validFrom: number;
+    validUntil: number;
+    hasUses: number;
+    uses: number;
+}
+
+export const issueGrantInstructionData = struct<IssueGrantInstructionData>([
+    u8('instruction'),
+    publicKey('grantee'),
+    ns64('validFrom'),
+    ns64('validUntil'),
+    u8('hasUses'),
+    u32('uses'),
+]);
+
+/**
+ *  Grant access to the door between two unix timestamps, signed by its key holder (the payer)
+ */
+export async function issueGrant(grantee: PublicKey, validFrom: number, validUntil: number, uses?: number): Promise<void> {
+  let [GrantPubkey] = await PublicKey.findProgramAddress(
+    [Buffer.from('grant', 'utf8'), DoorPubkey.toBuffer(), grantee.toBuffer()],
+    programId,
+  );
+  const data = Buffer.alloc(issueGrantInstructionData.span);
+  issueGrantInstructionData.encode(
+      {
+        instruction: 11,
+        grantee,
+        validFrom,
+        validUntil,
+        hasUses: uses === undefined ? 0 : 1,
+        uses: uses ?? 0,
+      },
+      data
+  );
+
+  const instruction = new TransactionInstruction({
+    keys: [
+      {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+      {pubkey: GrantPubkey, isSigner: false, isWritable: true},
+      {pubkey: DoorPubkey, isSigner: false, isWritable: false},
+      {pubkey: payer.publicKey, isSigner: true, isWritable: true},
+    ],
+    programId,
+    data: data, 
+  });
+
+  await sendAndConfirmTransaction(
+    connection,
+    new Transaction().add(instruction),
+    [payer],
+  );
+}
+
+
+
+/**
+ *  Replace the guardians of the config, signed by the admin (the payer)
+ */
+export async function setGuardians(guardians: PublicKey[]): Promise<void> {
+  let [ConfigPubkey, bump] = await PublicKey.findProgramAddress([Buffer.from('You pass butter', 'utf8')], programId);
+  const data = Buffer.concat([
+    Buffer.from([13, guardians.length]),
+    ...guardians.map(guardian => guardian.toBuffer()),
+  ]);
+
+  const instruction = new TransactionInstruction({
+    keys: [
+      {pubkey: ConfigPubkey, isSigner: false, isWritable: true},
+      {pubkey: payer.publicKey, isSigner: true, isWritable: false},
+    ],
+    programId,
+    data: data, 
+  });
+
+  await sendAndConfirmTransaction(
+    connection,
+    new Transaction().add(instruction),
+    [payer],
+  );
+}
+
+export interface EmergencyLockInstructionData {
+    instruction: number;
+}
+
+export const emergencyLockInstructionData = struct<EmergencyLockInstructionData>([
+    u8('instruction'),
+]);
+
+/**
+ *  Lock the config and close every door of it, signed by a guardian; the
+ *  given doors are marked closed in their own state as well, and their
+ *  access logs record the guardian closing them
+ */
+export async function emergencyLock(guardian: Keypair, doors: PublicKey[]): Promise<void> {
+  let [ConfigPubkey, bump] = await PublicKey.findProgramAddress([Buffer.from('You pass butter', 'utf8')], programId);
+  const data = Buffer.alloc(emergencyLockInstructionData.span);
+  emergencyLockInstructionData.encode(
+      {
+        instruction: 14,
+      },
+      data
+  );
+
+  const doorKeys = [];
+  for (const door of doors) {
+    const [log] = await PublicKey.findProgramAddress(
+      [Buffer.from('log', 'utf8'), door.toBuffer()],
+      programId,
+    );
+    doorKeys.push(
+      {pubkey: door, isSigner: false, isWritable: true},
+      {pubkey: log, isSigner: false, isWritable: true},
+    );
+  }
+
+  const instruction = new TransactionInstruction({
+    keys: [
+      {pubkey: ConfigPubkey, isSigner: false, isWritable: true},
+      {pubkey: guardian.publicKey, isSigner: true, isWritable: false},
+      ...doorKeys,
+    ],
+    programId,
+    data: data, 
+  });
+
+  await sendAndConfirmTransaction(
+    connection,
+    new Transaction().add(instruction),
+    [payer, guardian],
+  );
+}
+
+
+
 export interface OpenInstructionData {
     instruction: number;
 }
@@ -421,6 +662,7 @@ export async function open(): Promise<void> {
   const instruction = new TransactionInstruction({
     keys: [
       {pubkey: DoorPubkey, isSigner: false, isWritable: true},
+      {pubkey: AccessLogPubkey, isSigner: false, isWritable: true},
       {pubkey: ConfigPubkey, isSigner: false, isWritable: false},
       {pubkey: payer.publicKey, isSigner: true, isWritable: false},
     ],
@@ -462,6 +704,7 @@ export async function close(): Promise<void> {
   const instruction = new TransactionInstruction({
     keys: [
       {pubkey: DoorPubkey, isSigner: false, isWritable: true},
+      {pubkey: AccessLogPubkey, isSigner: false, isWritable: true},
       {pubkey: ConfigPubkey, isSigner: false, isWritable: false},
       {pubkey: payer.publicKey, isSigner: true, isWritable: false},
     ],
@@ -7,7 +7,7 @@ import {
   establishPayer,
   checkProgram,
   createConfig,
-  InitializeDoor,
+  createDoor,
   InitializeConfig,
   lock,
   unlock,
@@ -24,18 +24,17 @@ async function main() {
   // Determine who pays for the fees
   await establishPayer();
   
-  // check and create account for door
+  // check the program and derive the door address
   await checkProgram();
   
   // create Config_Account;
   await createConfig();
   
-  // Initialize Door 
-  //await InitializeDoor();
-
   // Initialize Account 
   await InitializeConfig();
 
+  // Create the door, once the config is initialized
+  //await createDoor();
 
   // Unlock the door
   //await unlock();
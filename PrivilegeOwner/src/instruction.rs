@@ -6,16 +6,11 @@ use solana_program::{
     program_error::ProgramError,
     pubkey::{Pubkey, PUBKEY_BYTES},
 };
-use std::mem::size_of;
+use std::{convert::TryInto, mem::size_of};
 
 /// Instructions.
 // #[derive(Clone, Debug, PartialEq)]
 pub enum DoorInstruction {
-    /// InitializeDoor
-    InitializeDoor {
-       /// pubkey
-       key: Pubkey
-    },
     ///
     InitializeConfig {
        /// pubkey
@@ -26,11 +21,79 @@ pub enum DoorInstruction {
     /// unlock
     Unlock,
     /// Open
+    ///
+    /// Accounts: door, access log, config, key holder or grantee, then the
+    /// grant if opened by a grantee.
     Open,
     /// Close
+    ///
+    /// Accounts: door, access log, config, key holder or grantee, then the
+    /// grant if closed by a grantee.
     Close,
     /// AllocatePDA
-    AllocatePDA
+    AllocatePDA,
+    /// Create a door governed by the config, signed and paid for by its admin.
+    ///
+    /// Also creates the access log of the door.
+    ///
+    /// Accounts: system program, door PDA, access log PDA, config, admin.
+    CreateDoor {
+       /// The key that can open the door
+       key: Pubkey,
+       /// Index distinguishing the doors of one key under the config
+       index: u64,
+    },
+    /// Propose a new admin, signed by the current admin. Replaces any
+    /// pending proposal.
+    ///
+    /// Accounts: config, admin.
+    ProposeAdmin {
+       /// The proposed admin
+       key: Pubkey,
+    },
+    /// Become the admin, signed by the pending admin.
+    ///
+    /// Accounts: config, pending admin.
+    AcceptAdmin,
+    /// Withdraw the pending proposal, signed by the current admin.
+    ///
+    /// Accounts: config, admin.
+    CancelAdminTransfer,
+    /// Grant access to a door, signed and paid for by its key holder.
+    /// Re-issuing a grant replaces its terms.
+    ///
+    /// Accounts: system program, grant PDA, door, key holder.
+    IssueGrant {
+       /// The key the door is delegated to
+       grantee: Pubkey,
+       /// Unix timestamp from which the grant can be used
+       valid_from: i64,
+       /// Unix timestamp from which the grant can no longer be used
+       valid_until: i64,
+       /// Number of times the door can be opened with the grant, if limited;
+       /// at least one
+       uses: Option<u32>,
+    },
+    /// Revoke a grant, signed by the key holder of the door, who receives
+    /// its lamports.
+    ///
+    /// Accounts: grant, door, key holder.
+    RevokeGrant,
+    /// Replace the guardians of the config, signed by the admin. The
+    /// guardians must be non-zero and unique.
+    ///
+    /// Accounts: config, admin.
+    SetGuardians {
+       /// The new guardians
+       guardians: Vec<Pubkey>,
+    },
+    /// Lock the config and close every door of it, signed by a guardian.
+    /// Only the admin can unlock.
+    ///
+    /// Accounts: config, guardian, then any doors of the config, each
+    /// followed by its access log, to mark closed in their own state as
+    /// well and log the close of those that were open.
+    EmergencyLock,
 }
 
 impl DoorInstruction {
@@ -38,10 +101,6 @@ impl DoorInstruction {
     pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
         let (&tag, rest) = input.split_first().ok_or(0)?;
         Ok(match tag {
-            0 => {
-                let (key, _rest) = Self::unpack_pubkey(rest)?;
-                Self::InitializeDoor { key }
-            }
             1 => {
                 let (key, _rest) = Self::unpack_pubkey(rest)?;
                 Self::InitializeConfig { key }
@@ -61,6 +120,50 @@ impl DoorInstruction {
             6 => {
                 Self::AllocatePDA
             }
+            7 => {
+                let (key, rest) = Self::unpack_pubkey(rest)?;
+                let (index, _rest) = Self::unpack_u64(rest)?;
+                Self::CreateDoor { key, index }
+            }
+            8 => {
+                let (key, _rest) = Self::unpack_pubkey(rest)?;
+                Self::ProposeAdmin { key }
+            }
+            9 => {
+                Self::AcceptAdmin
+            }
+            10 => {
+                Self::CancelAdminTransfer
+            }
+            11 => {
+                let (grantee, rest) = Self::unpack_pubkey(rest)?;
+                let (valid_from, rest) = Self::unpack_i64(rest)?;
+                let (valid_until, rest) = Self::unpack_i64(rest)?;
+                let (&has_uses, rest) = rest.split_first().ok_or(ProgramError::InvalidArgument)?;
+                let (uses, _rest) = Self::unpack_u32(rest)?;
+                let uses = match has_uses {
+                    0 => None,
+                    1 => Some(uses),
+                    _ => return Err(ProgramError::InvalidArgument),
+                };
+                Self::IssueGrant { grantee, valid_from, valid_until, uses }
+            }
+            12 => {
+                Self::RevokeGrant
+            }
+            13 => {
+                let (&n, mut rest) = rest.split_first().ok_or(ProgramError::InvalidArgument)?;
+                let mut guardians = Vec::with_capacity(n as usize);
+                for _ in 0..n {
+                    let (guardian, _rest) = Self::unpack_pubkey(rest)?;
+                    guardians.push(guardian);
+                    rest = _rest;
+                }
+                Self::SetGuardians { guardians }
+            }
+            14 => {
+                Self::EmergencyLock
+            }
             _ => {
                 return Err(ProgramError::InvalidArgument)
             }
@@ -76,6 +179,45 @@ impl DoorInstruction {
         let pk = Pubkey::new(key);
         Ok((pk, rest))
     }
+    /// unpack u32
+    pub fn unpack_u32(input: &[u8]) -> Result<(u32, &[u8]), ProgramError> {
+        if input.len() < 4 {
+            msg!("u32 cannot be unpacked");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let (value, rest) = input.split_at(4);
+        let value = value
+            .try_into()
+            .map(u32::from_le_bytes)
+            .map_err(|_| ProgramError::InvalidArgument)?;
+        Ok((value, rest))
+    }
+    /// unpack i64
+    pub fn unpack_i64(input: &[u8]) -> Result<(i64, &[u8]), ProgramError> {
+        if input.len() < 8 {
+            msg!("i64 cannot be unpacked");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let (value, rest) = input.split_at(8);
+        let value = value
+            .try_into()
+            .map(i64::from_le_bytes)
+            .map_err(|_| ProgramError::InvalidArgument)?;
+        Ok((value, rest))
+    }
+    /// unpack u64
+    pub fn unpack_u64(input: &[u8]) -> Result<(u64, &[u8]), ProgramError> {
+        if input.len() < 8 {
+            msg!("u64 cannot be unpacked");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let (value, rest) = input.split_at(8);
+        let value = value
+            .try_into()
+            .map(u64::from_le_bytes)
+            .map_err(|_| ProgramError::InvalidArgument)?;
+        Ok((value, rest))
+    }
     
 
 }
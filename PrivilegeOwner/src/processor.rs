@@ -1,5 +1,5 @@
 //! Program instruction processor
-use crate::{state::{Door, Config}, instruction::DoorInstruction};
+use crate::{state::{Door, Config, Grant, AccessAction, AccessLog, AccessLogEntry, ACCESS_LOG_SEED, DOOR_SEED, GRANT_SEED, MAX_GUARDIANS}, instruction::DoorInstruction};
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     borsh::try_from_slice_unchecked,
@@ -10,14 +10,13 @@ use solana_program::{
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack, Sealed},
     system_instruction,
-    program::invoke_signed,
+    program::{invoke, invoke_signed},
+    sysvar::{clock::Clock, rent::Rent, Sysvar},
+    system_program,
 };
 use std::convert::TryInto;
 
 
-/// Size of PDA 
-pub const SIZE: usize = 1024;
-
 /// Instruction processor
 pub fn process_instruction(
     program_id: &Pubkey,
@@ -27,12 +26,6 @@ pub fn process_instruction(
     let instruction = DoorInstruction::unpack(instruction_data)?;
 
     match instruction {
-        DoorInstruction::InitializeDoor {
-             key
-        } => {
-            msg!("Instruction: InitializeDoor");
-            InitializeDoor(accounts, key)
-        }
         DoorInstruction::InitializeConfig {
              key
         } => {
@@ -59,32 +52,118 @@ pub fn process_instruction(
             msg!("Instruction: Allocate");
             AllocatePDA(program_id, accounts)
         }
+        DoorInstruction::CreateDoor {
+             key,
+             index
+        } => {
+            msg!("Instruction: CreateDoor");
+            CreateDoor(program_id, accounts, key, index)
+        }
+        DoorInstruction::ProposeAdmin {
+             key
+        } => {
+            msg!("Instruction: ProposeAdmin");
+            ProposeAdmin(program_id, accounts, key)
+        }
+        DoorInstruction::AcceptAdmin => {
+            msg!("Instruction: AcceptAdmin");
+            AcceptAdmin(program_id, accounts)
+        }
+        DoorInstruction::CancelAdminTransfer => {
+            msg!("Instruction: CancelAdminTransfer");
+            CancelAdminTransfer(program_id, accounts)
+        }
+        DoorInstruction::IssueGrant {
+             grantee,
+             valid_from,
+             valid_until,
+             uses
+        } => {
+            msg!("Instruction: IssueGrant");
+            IssueGrant(program_id, accounts, grantee, valid_from, valid_until, uses)
+        }
+        DoorInstruction::RevokeGrant => {
+            msg!("Instruction: RevokeGrant");
+            RevokeGrant(program_id, accounts)
+        }
+        DoorInstruction::SetGuardians {
+             guardians
+        } => {
+            msg!("Instruction: SetGuardians");
+            SetGuardians(program_id, accounts, guardians)
+        }
+        DoorInstruction::EmergencyLock => {
+            msg!("Instruction: EmergencyLock");
+            EmergencyLock(program_id, accounts)
+        }
      }
 }
 
 
-/// Initialize Door
-pub fn InitializeDoor(
+/// Create Door
+pub fn CreateDoor(
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
     key: Pubkey,
+    index: u64,
 ) -> ProgramResult {
-     let account_info_iter = &mut accounts.iter();
-     let door_info = next_account_info(account_info_iter)?;
-     
-     /// deserializing 
-     let mut door = Door::unpack_unchecked(&door_info.data.borrow())?;
-     if door.is_initialized {
+    let account_info_iter = &mut accounts.iter();
+    let system_program_info = next_account_info(account_info_iter)?;
+    let door_info = next_account_info(account_info_iter)?;
+    let log_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    check_account_owner(program_id, config_info)?;
+    // deserializing
+    let config = Config::unpack(&config_info.data.borrow())?;
+    validate_owner(&config.admin, admin_info)?;
+
+    let (expected_allocated_key, bump) =
+        Door::find_address(program_id, config_info.key, &key, index);
+    if *door_info.key != expected_allocated_key {
         return Err(ProgramError::InvalidArgument);
-     }
-     
-     door.key = key;
-     door.is_initialized = true;
-     door.is_opened = false;
-     
-     /// serializing
-     Door::pack(door, &mut door_info.data.borrow_mut())?;
+    }
+
+    create_pda_account(
+        admin_info,
+        door_info,
+        system_program_info,
+        Door::LEN,
+        program_id,
+        &[DOOR_SEED, config_info.key.as_ref(), key.as_ref(), &index.to_le_bytes(), &[bump]],
+    )?;
 
-     Ok(())
+    let door = Door {
+        key,
+        is_initialized: true,
+        is_opened: false,
+        config: *config_info.key,
+        index,
+        bump,
+        emergency_epoch: config.emergency_epoch,
+    };
+
+    // serializing
+    Door::pack(door, &mut door_info.data.borrow_mut())?;
+
+    let (expected_log_key, log_bump) = AccessLog::find_address(program_id, door_info.key);
+    if *log_info.key != expected_log_key {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    create_pda_account(
+        admin_info,
+        log_info,
+        system_program_info,
+        AccessLog::LEN,
+        program_id,
+        &[ACCESS_LOG_SEED, door_info.key.as_ref(), &[log_bump]],
+    )?;
+
+    AccessLog::pack(AccessLog::new(*door_info.key, log_bump), &mut log_info.data.borrow_mut())?;
+
+    Ok(())
 }
 
 /// Initialize Config
@@ -112,6 +191,10 @@ pub fn InitializeConfig(
     config.admin = key;
     config.is_locked = true;
     config.is_initialized = true;
+    config.pending_admin = None;
+    config.is_emergency = false;
+    config.guardian_count = 0;
+    config.emergency_epoch = 0;
      
     /// serializing
     Config::pack(config, &mut config_info.data.borrow_mut())?;
@@ -119,6 +202,149 @@ pub fn InitializeConfig(
     Ok(())
 }
 
+/// Propose a new admin
+pub fn ProposeAdmin(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    key: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    check_account_owner(program_id, config_info)?;
+    // deserializing
+    let mut config = Config::unpack(&config_info.data.borrow())?;
+    validate_owner(&config.admin, admin_info)?;
+
+    config.pending_admin = Some(key);
+
+    // serializing
+    Config::pack(config, &mut config_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+/// Accept the admin role as the pending admin
+pub fn AcceptAdmin(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let pending_admin_info = next_account_info(account_info_iter)?;
+
+    check_account_owner(program_id, config_info)?;
+    // deserializing
+    let mut config = Config::unpack(&config_info.data.borrow())?;
+    let pending_admin = config.pending_admin.ok_or(ProgramError::InvalidArgument)?;
+    validate_owner(&pending_admin, pending_admin_info)?;
+
+    config.admin = pending_admin;
+    config.pending_admin = None;
+
+    // serializing
+    Config::pack(config, &mut config_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+/// Cancel the pending admin transfer
+pub fn CancelAdminTransfer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    check_account_owner(program_id, config_info)?;
+    // deserializing
+    let mut config = Config::unpack(&config_info.data.borrow())?;
+    validate_owner(&config.admin, admin_info)?;
+    if config.pending_admin.is_none() {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    config.pending_admin = None;
+
+    // serializing
+    Config::pack(config, &mut config_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+/// Set the guardians
+pub fn SetGuardians(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    guardians: Vec<Pubkey>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    check_account_owner(program_id, config_info)?;
+    // deserializing
+    let mut config = Config::unpack(&config_info.data.borrow())?;
+    validate_owner(&config.admin, admin_info)?;
+    validate_guardians(&guardians)?;
+
+    config.guardians = [Pubkey::default(); MAX_GUARDIANS];
+    config.guardians[0..guardians.len()].copy_from_slice(&guardians);
+    config.guardian_count = guardians.len() as u8;
+
+    // serializing
+    Config::pack(config, &mut config_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+/// Emergency lock, closing every door of the config
+pub fn EmergencyLock(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let guardian_info = next_account_info(account_info_iter)?;
+
+    check_account_owner(program_id, config_info)?;
+    // deserializing
+    let mut config = Config::unpack(&config_info.data.borrow())?;
+    if !config.is_guardian(guardian_info.key) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if !guardian_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    while let Some(door_info) = account_info_iter.next() {
+        let log_info = next_account_info(account_info_iter)?;
+        check_account_owner(program_id, door_info)?;
+        let mut door = Door::unpack(&door_info.data.borrow())?;
+        if !cmp_pubkeys(&door.config, config_info.key) {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let was_open = door.is_open(&config);
+        door.is_opened = false;
+        Door::pack(door, &mut door_info.data.borrow_mut())?;
+
+        if was_open {
+            record_access(program_id, log_info, door_info.key, guardian_info.key, AccessAction::Close)?;
+        }
+    }
+
+    config.is_locked = true;
+    config.is_emergency = true;
+    config.emergency_epoch = config.emergency_epoch.wrapping_add(1);
+
+    // serializing
+    Config::pack(config, &mut config_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
 /// Lock the door 
 pub fn Lock(
     program_id: &Pubkey,
@@ -184,6 +410,7 @@ pub fn Unlock(
     } 
 
     config.is_locked = false;
+    config.is_emergency = false;
     
     Config::pack(config, &mut config_info.data.borrow_mut())?;
 
@@ -198,6 +425,7 @@ pub fn Open(
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let door_info = next_account_info(account_info_iter)?;
+    let log_info = next_account_info(account_info_iter)?;
     let config_info = next_account_info(account_info_iter)?;
     let owner_info = next_account_info(account_info_iter)?;
     
@@ -212,22 +440,27 @@ pub fn Open(
     } 
     
 
-    let mut door = Door::unpack_unchecked(&door_info.data.borrow())?;
-
-    let expected_owner = door.key;
+    let mut door = Door::unpack(&door_info.data.borrow())?;
+    if !cmp_pubkeys(&door.config, config_info.key) {
+        return Err(ProgramError::InvalidArgument);
+    }
 
-    validate_owner(&expected_owner, owner_info)?;
+    // opening with a grant uses it up
+    validate_access(program_id, door_info.key, &door, owner_info, account_info_iter, true)?;
     
-    if  door.is_opened {
+    if door.is_open(&config) {
         return Err(ProgramError::InvalidArgument);
     }
 
     door.is_opened = true;
+    door.emergency_epoch = config.emergency_epoch;
 
     Door::pack(
         door,
         &mut door_info.data.borrow_mut()
     )?;
+
+    record_access(program_id, log_info, door_info.key, owner_info.key, AccessAction::Open)?;
     
     Ok(())
 }    
@@ -239,6 +472,7 @@ pub fn Close(
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let door_info = next_account_info(account_info_iter)?;
+    let log_info = next_account_info(account_info_iter)?;
     let config_info = next_account_info(account_info_iter)?;
     let owner_info = next_account_info(account_info_iter)?;
     
@@ -252,13 +486,14 @@ pub fn Close(
         return Err(ProgramError::InvalidArgument);
     } 
 
-    let mut door = Door::unpack_unchecked(&door_info.data.borrow())?;
-
-    let expected_owner = door.key;
+    let mut door = Door::unpack(&door_info.data.borrow())?;
+    if !cmp_pubkeys(&door.config, config_info.key) {
+        return Err(ProgramError::InvalidArgument);
+    }
 
-    validate_owner(&expected_owner, owner_info);
+    validate_access(program_id, door_info.key, &door, owner_info, account_info_iter, false)?;
     
-    if !door.is_opened {
+    if !door.is_open(&config) {
         return Err(ProgramError::InvalidArgument);
     }
 
@@ -268,6 +503,8 @@ pub fn Close(
         door,
         &mut door_info.data.borrow_mut()
     )?;
+
+    record_access(program_id, log_info, door_info.key, owner_info.key, AccessAction::Close)?;
     
     Ok(())
 } 
@@ -296,26 +533,250 @@ pub fn AllocatePDA(
         return Err(ProgramError::InvalidArgument);
     }
 
-    // Invoke the system program to allocate account data
-    invoke_signed(
-        &system_instruction::create_account(
-            &owner_info.key,
-            &allocated_info.key,
-            /// lamports
-            80179200,
-            SIZE as u64,
-            &program_id, 
+    create_pda_account(
+        owner_info,
+        allocated_info,
+        system_program_info,
+        Config::LEN,
+        program_id,
+        &[b"You pass butter", &[bump]],
+    )?;
+
+    Ok(())
+}
+
+/// Creates the PDA `allocated_info` with `space` bytes owned by `owner`,
+/// funded by `payer_info` with exactly the rent-exempt minimum. If the
+/// address already holds lamports, which would make `create_account` fail,
+/// it is topped up, then allocated and assigned instead.
+pub fn create_pda_account<'a>(
+    payer_info: &AccountInfo<'a>,
+    allocated_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    space: usize,
+    owner: &Pubkey,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    let required_lamports = Rent::get()?.minimum_balance(space);
+    let current_lamports = allocated_info.lamports();
+    let account_infos = &[
+        payer_info.clone(),
+        system_program_info.clone(),
+        allocated_info.clone(),
+    ];
+
+    if current_lamports == 0 {
+        return invoke_signed(
+            &system_instruction::create_account(
+                payer_info.key,
+                allocated_info.key,
+                required_lamports,
+                space as u64,
+                owner,
             ),
-        // Order doesn't matter and this slice could include all the accounts and be:
-        // `&accounts`
-        &[  owner_info.clone(),
-            system_program_info.clone(), // program being invoked also needs to be included
-            allocated_info.clone(),
-        ],
-
-        &[&[b"You pass butter", &[bump]]],
+            account_infos,
+            &[signer_seeds],
+        );
+    }
+
+    let top_up = required_lamports.saturating_sub(current_lamports);
+    if top_up > 0 {
+        invoke(
+            &system_instruction::transfer(payer_info.key, allocated_info.key, top_up),
+            account_infos,
+        )?;
+    }
+    invoke_signed(
+        &system_instruction::allocate(allocated_info.key, space as u64),
+        account_infos,
+        &[signer_seeds],
     )?;
+    invoke_signed(
+        &system_instruction::assign(allocated_info.key, owner),
+        account_infos,
+        &[signer_seeds],
+    )
+}
+
+/// Issue Grant
+pub fn IssueGrant(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    grantee: Pubkey,
+    valid_from: i64,
+    valid_until: i64,
+    uses: Option<u32>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let system_program_info = next_account_info(account_info_iter)?;
+    let grant_info = next_account_info(account_info_iter)?;
+    let door_info = next_account_info(account_info_iter)?;
+    let key_info = next_account_info(account_info_iter)?;
+
+    check_account_owner(program_id, door_info)?;
+    // deserializing
+    let door = Door::unpack(&door_info.data.borrow())?;
+    validate_owner(&door.key, key_info)?;
+
+    if valid_from >= valid_until {
+        return Err(ProgramError::InvalidArgument);
+    }
+    // a grant without uses could never open the door
+    if uses == Some(0) {
+        return Err(ProgramError::InvalidArgument);
+    }
 
+    let (expected_allocated_key, bump) = Grant::find_address(program_id, door_info.key, &grantee);
+    if *grant_info.key != expected_allocated_key {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // a grant issued before is still owned by the program and only gets new terms
+    if !cmp_pubkeys(program_id, grant_info.owner) {
+        create_pda_account(
+            key_info,
+            grant_info,
+            system_program_info,
+            Grant::LEN,
+            program_id,
+            &[GRANT_SEED, door_info.key.as_ref(), grantee.as_ref(), &[bump]],
+        )?;
+    }
+
+    let grant = Grant {
+        door: *door_info.key,
+        grantee,
+        valid_from,
+        valid_until,
+        remaining_uses: uses,
+        is_initialized: true,
+        bump,
+    };
+
+    // serializing
+    Grant::pack(grant, &mut grant_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+/// Revoke Grant
+pub fn RevokeGrant(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let grant_info = next_account_info(account_info_iter)?;
+    let door_info = next_account_info(account_info_iter)?;
+    let key_info = next_account_info(account_info_iter)?;
+
+    check_account_owner(program_id, grant_info)?;
+    check_account_owner(program_id, door_info)?;
+    // deserializing
+    let grant = Grant::unpack(&grant_info.data.borrow())?;
+    let door = Door::unpack(&door_info.data.borrow())?;
+    if !cmp_pubkeys(&grant.door, door_info.key) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    validate_owner(&door.key, key_info)?;
+
+    // hand the lamports back to the key holder and return the account to the system program
+    let lamports = grant_info.lamports();
+    **key_info.lamports.borrow_mut() = key_info
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::InvalidArgument)?;
+    **grant_info.lamports.borrow_mut() = 0;
+    sol_memset(&mut grant_info.data.borrow_mut(), 0, Grant::LEN);
+    grant_info.realloc(0, false)?;
+    grant_info.assign(&system_program::id());
+
+    Ok(())
+}
+
+/// Checks that `owner_info` may use the door: either it is the door key, or
+/// the next account is a grant of the door to it, valid at the current time.
+/// `consume` counts this as one use of the grant.
+pub fn validate_access<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    door_key: &Pubkey,
+    door: &Door,
+    owner_info: &AccountInfo<'b>,
+    account_info_iter: &mut impl Iterator<Item = &'a AccountInfo<'b>>,
+    consume: bool,
+) -> ProgramResult {
+    if cmp_pubkeys(&door.key, owner_info.key) {
+        return validate_owner(&door.key, owner_info);
+    }
+
+    // neither the key holder nor a grantee presenting a grant
+    let grant_info = account_info_iter
+        .next()
+        .ok_or(ProgramError::MissingRequiredSignature)?;
+    check_account_owner(program_id, grant_info)?;
+    let mut grant = Grant::unpack(&grant_info.data.borrow())?;
+    if !cmp_pubkeys(&grant.door, door_key) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    validate_owner(&grant.grantee, owner_info)?;
+    if !grant.is_valid_at(Clock::get()?.unix_timestamp) {
+        msg!("Grant is not valid at this time");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if consume {
+        if let Some(remaining_uses) = grant.remaining_uses {
+            grant.remaining_uses = Some(
+                remaining_uses
+                    .checked_sub(1)
+                    .ok_or(ProgramError::InvalidArgument)?,
+            );
+            Grant::pack(grant, &mut grant_info.data.borrow_mut())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Append an access to the log of the door
+pub fn record_access(
+    program_id: &Pubkey,
+    log_info: &AccountInfo,
+    door_key: &Pubkey,
+    actor: &Pubkey,
+    action: AccessAction,
+) -> ProgramResult {
+    check_account_owner(program_id, log_info)?;
+    let mut log = AccessLog::unpack(&log_info.data.borrow())?;
+    if !cmp_pubkeys(&log.door, door_key) {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let clock = Clock::get()?;
+    log.push(AccessLogEntry {
+        actor: *actor,
+        action,
+        slot: clock.slot,
+        unix_timestamp: clock.unix_timestamp,
+    });
+
+    AccessLog::pack(log, &mut log_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+/// Checks that the guardians are at most `MAX_GUARDIANS`, non-zero and unique
+pub fn validate_guardians(guardians: &[Pubkey]) -> ProgramResult {
+    if guardians.len() > MAX_GUARDIANS {
+        return Err(ProgramError::InvalidArgument);
+    }
+    for (i, guardian) in guardians.iter().enumerate() {
+        if *guardian == Pubkey::default() {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if guardians[..i].iter().any(|other| cmp_pubkeys(other, guardian)) {
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
     Ok(())
 }
 
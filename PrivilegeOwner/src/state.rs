@@ -10,6 +10,21 @@ use solana_program::{
 
 
 
+/// Seed prefix of every door PDA, followed by the config, the door key and the door index
+pub const DOOR_SEED: &[u8] = b"door";
+
+/// Maximum number of guardians of a config
+pub const MAX_GUARDIANS: usize = 5;
+
+/// Seed prefix of every grant PDA, followed by the door and the grantee
+pub const GRANT_SEED: &[u8] = b"grant";
+
+/// Seed prefix of every access log PDA, followed by the door
+pub const ACCESS_LOG_SEED: &[u8] = b"log";
+
+/// Number of entries an access log keeps before overwriting the oldest
+pub const ACCESS_LOG_CAPACITY: usize = 32;
+
 /// Door 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct Door {
@@ -18,9 +33,35 @@ pub struct Door {
     /// Default to false  
     pub is_initialized: bool,
     /// Default to closed 
-    pub is_opened: bool 
+    pub is_opened: bool,
+    /// The config governing the door
+    pub config: Pubkey,
+    /// Index distinguishing the doors of one key under the config
+    pub index: u64,
+    /// Bump seed of the door address
+    pub bump: u8,
+    /// Emergency epoch of the config when the door was opened
+    pub emergency_epoch: u64,
 }
 
+impl Door {
+    /// Derives the address of the door of `key` with `index` under `config`
+    pub fn find_address(program_id: &Pubkey, config: &Pubkey, key: &Pubkey, index: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[DOOR_SEED, config.as_ref(), key.as_ref(), &index.to_le_bytes()],
+            program_id,
+        )
+    }
+
+    /// Returns whether the door is open: an emergency lock of its config
+    /// closes every door opened before it
+    pub fn is_open(&self, config: &Config) -> bool {
+        self.is_opened && self.emergency_epoch == config.emergency_epoch
+    }
+}
+
+// program accounts are told apart by their size
+const _: () = assert!(Door::LEN != Config::LEN);
 
 impl Sealed for Door {}
 impl IsInitialized for Door {
@@ -30,11 +71,11 @@ impl IsInitialized for Door {
 }
 
 impl Pack for Door {
-    const LEN: usize = 1024;
+    const LEN: usize = 83;
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let src = array_ref![src, 0, 34];
-        let (key, is_initialized, is_opened) =
-            array_refs![src, 32, 1, 1];
+        let src = array_ref![src, 0, Door::LEN];
+        let (key, is_initialized, is_opened, config, index, bump, emergency_epoch) =
+            array_refs![src, 32, 1, 1, 32, 8, 1, 8];
         let is_initialized = match is_initialized {
             [0] => false,
             [1] => true,
@@ -51,22 +92,41 @@ impl Pack for Door {
             key: Pubkey::new_from_array(*key),
             is_initialized,
             is_opened,
+            config: Pubkey::new_from_array(*config),
+            index: u64::from_le_bytes(*index),
+            bump: bump[0],
+            emergency_epoch: u64::from_le_bytes(*emergency_epoch),
         })
     }
     fn pack_into_slice(&self, dst: &mut [u8]) {
-        let dst = array_mut_ref![dst, 0, 34];
-        let (key_dst, is_initialized_dst, is_opened_dst) = 
-             mut_array_refs![dst, 32, 1, 1];
+        let dst = array_mut_ref![dst, 0, Door::LEN];
+        let (
+            key_dst,
+            is_initialized_dst,
+            is_opened_dst,
+            config_dst,
+            index_dst,
+            bump_dst,
+            emergency_epoch_dst,
+        ) = mut_array_refs![dst, 32, 1, 1, 32, 8, 1, 8];
 
         let &Door {
             ref key,
             is_initialized,
             is_opened,
+            ref config,
+            index,
+            bump,
+            emergency_epoch,
         } = self;
 
         key_dst.copy_from_slice(key.as_ref());
         is_initialized_dst[0] = is_initialized as u8;
         is_opened_dst[0] = is_opened as u8;
+        config_dst.copy_from_slice(config.as_ref());
+        *index_dst = index.to_le_bytes();
+        bump_dst[0] = bump;
+        *emergency_epoch_dst = emergency_epoch.to_le_bytes();
     }
 }
 
@@ -81,7 +141,32 @@ pub struct Config {
     pub is_locked: bool,
 
     /// Default to false  
-    pub is_initialized: bool
+    pub is_initialized: bool,
+
+    /// The admin proposed by the current admin, until they accept
+    pub pending_admin: Option<Pubkey>,
+
+    /// Locked by a guardian; only the admin can unlock
+    pub is_emergency: bool,
+
+    /// Number of guardians
+    pub guardian_count: u8,
+
+    /// Keys that can trigger an emergency lock
+    pub guardians: [Pubkey; MAX_GUARDIANS],
+
+    /// Number of emergency locks so far; doors opened before the latest
+    /// one are closed
+    pub emergency_epoch: u64,
+}
+
+impl Config {
+    /// Returns whether `key` is one of the guardians
+    pub fn is_guardian(&self, key: &Pubkey) -> bool {
+        self.guardians[0..self.guardian_count as usize]
+            .iter()
+            .any(|guardian| guardian == key)
+    }
 }
 
 
@@ -93,11 +178,21 @@ impl IsInitialized for Config {
 }
 
 impl Pack for Config {
-    const LEN: usize = 1024;
+    const LEN: usize = 237;
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let src = array_ref![src, 0, 34];
-        let (admin, is_locked, is_initialized) =
-            array_refs![src, 32, 1, 1];
+        let src = array_ref![src, 0, Config::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            admin,
+            is_locked,
+            is_initialized,
+            has_pending_admin,
+            pending_admin,
+            is_emergency,
+            guardian_count,
+            guardians_flat,
+            emergency_epoch,
+        ) = array_refs![src, 32, 1, 1, 1, 32, 1, 1, 32 * MAX_GUARDIANS, 8];
         
         let is_locked = match is_locked {
             [0] => false,
@@ -111,25 +206,329 @@ impl Pack for Config {
             _ => return Err(ProgramError::InvalidAccountData),
         };
 
+        let pending_admin = match has_pending_admin {
+            [0] => None,
+            [1] => Some(Pubkey::new_from_array(*pending_admin)),
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        let is_emergency = match is_emergency {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        if guardian_count[0] as usize > MAX_GUARDIANS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut guardians = [Pubkey::default(); MAX_GUARDIANS];
+        for (src, dst) in guardians_flat.chunks(32).zip(guardians.iter_mut()) {
+            *dst = Pubkey::new_from_array(*array_ref![src, 0, 32]);
+        }
+
         Ok(Config {
             admin: Pubkey::new_from_array(*admin),
             is_locked: is_locked,
             is_initialized: is_initialized,
+            pending_admin,
+            is_emergency,
+            guardian_count: guardian_count[0],
+            guardians,
+            emergency_epoch: u64::from_le_bytes(*emergency_epoch),
         })
     }
     fn pack_into_slice(&self, dst: &mut [u8]) {
-        let dst = array_mut_ref![dst, 0, 34];
-        let (admin_dst, is_locked_dst, is_initialized_dst) = 
-             mut_array_refs![dst, 32, 1, 1];
+        let dst = array_mut_ref![dst, 0, Config::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            admin_dst,
+            is_locked_dst,
+            is_initialized_dst,
+            has_pending_admin_dst,
+            pending_admin_dst,
+            is_emergency_dst,
+            guardian_count_dst,
+            guardians_flat,
+            emergency_epoch_dst,
+        ) = mut_array_refs![dst, 32, 1, 1, 1, 32, 1, 1, 32 * MAX_GUARDIANS, 8];
 
         let &Config {
             ref admin,
             is_locked,
             is_initialized,
+            ref pending_admin,
+            is_emergency,
+            guardian_count,
+            ref guardians,
+            emergency_epoch,
         } = self;
 
         admin_dst.copy_from_slice(admin.as_ref());
         is_locked_dst[0] = is_locked as u8;
         is_initialized_dst[0] = is_initialized as u8;
+        has_pending_admin_dst[0] = pending_admin.is_some() as u8;
+        pending_admin_dst.copy_from_slice(pending_admin.unwrap_or_default().as_ref());
+        is_emergency_dst[0] = is_emergency as u8;
+        guardian_count_dst[0] = guardian_count;
+        for (i, guardian) in guardians.iter().enumerate() {
+            let dst_array = array_mut_ref![guardians_flat, 32 * i, 32];
+            dst_array.copy_from_slice(guardian.as_ref());
+        }
+        *emergency_epoch_dst = emergency_epoch.to_le_bytes();
+    }
+}
+
+/// Access to a door delegated by its key holder for a limited time
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct Grant {
+    /// The door the grant opens
+    pub door: Pubkey,
+    /// The key that may use the grant
+    pub grantee: Pubkey,
+    /// Unix timestamp from which the grant can be used
+    pub valid_from: i64,
+    /// Unix timestamp from which the grant can no longer be used
+    pub valid_until: i64,
+    /// Number of times the door can still be opened with the grant, if
+    /// limited. Closing the door does not count as a use.
+    pub remaining_uses: Option<u32>,
+    /// Default to false
+    pub is_initialized: bool,
+    /// Bump seed of the grant address
+    pub bump: u8,
+}
+
+impl Grant {
+    /// Derives the address of the grant of `door` to `grantee`
+    pub fn find_address(program_id: &Pubkey, door: &Pubkey, grantee: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[GRANT_SEED, door.as_ref(), grantee.as_ref()], program_id)
+    }
+
+    /// Returns whether `now` lies within the validity window of the grant
+    pub fn is_valid_at(&self, now: i64) -> bool {
+        self.valid_from <= now && now < self.valid_until
+    }
+}
+
+const _: () = assert!(Grant::LEN != Door::LEN && Grant::LEN != Config::LEN);
+
+impl Sealed for Grant {}
+impl IsInitialized for Grant {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for Grant {
+    const LEN: usize = 87;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, Grant::LEN];
+        let (door, grantee, valid_from, valid_until, has_remaining_uses, remaining_uses, is_initialized, bump) =
+            array_refs![src, 32, 32, 8, 8, 1, 4, 1, 1];
+
+        let remaining_uses = match has_remaining_uses {
+            [0] => None,
+            [1] => Some(u32::from_le_bytes(*remaining_uses)),
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        let is_initialized = match is_initialized {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(Grant {
+            door: Pubkey::new_from_array(*door),
+            grantee: Pubkey::new_from_array(*grantee),
+            valid_from: i64::from_le_bytes(*valid_from),
+            valid_until: i64::from_le_bytes(*valid_until),
+            remaining_uses,
+            is_initialized,
+            bump: bump[0],
+        })
+    }
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, Grant::LEN];
+        let (
+            door_dst,
+            grantee_dst,
+            valid_from_dst,
+            valid_until_dst,
+            has_remaining_uses_dst,
+            remaining_uses_dst,
+            is_initialized_dst,
+            bump_dst,
+        ) = mut_array_refs![dst, 32, 32, 8, 8, 1, 4, 1, 1];
+
+        door_dst.copy_from_slice(self.door.as_ref());
+        grantee_dst.copy_from_slice(self.grantee.as_ref());
+        *valid_from_dst = self.valid_from.to_le_bytes();
+        *valid_until_dst = self.valid_until.to_le_bytes();
+        has_remaining_uses_dst[0] = self.remaining_uses.is_some() as u8;
+        *remaining_uses_dst = self.remaining_uses.unwrap_or_default().to_le_bytes();
+        is_initialized_dst[0] = self.is_initialized as u8;
+        bump_dst[0] = self.bump;
+    }
+}
+
+/// What was done to a door
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum AccessAction {
+    /// The door was opened
+    #[default]
+    Open,
+    /// The door was closed
+    Close,
+}
+
+/// One access to a door
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct AccessLogEntry {
+    /// The key holder, grantee or guardian that signed
+    pub actor: Pubkey,
+    /// What was done
+    pub action: AccessAction,
+    /// Slot of the access
+    pub slot: u64,
+    /// Unix timestamp of the access
+    pub unix_timestamp: i64,
+}
+
+impl AccessLogEntry {
+    const LEN: usize = 32 + 1 + 8 + 8;
+
+    fn unpack_from_slice(src: &[u8; AccessLogEntry::LEN]) -> Result<Self, ProgramError> {
+        let (actor, action, slot, unix_timestamp) = array_refs![src, 32, 1, 8, 8];
+
+        let action = match action {
+            [0] => AccessAction::Open,
+            [1] => AccessAction::Close,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(AccessLogEntry {
+            actor: Pubkey::new_from_array(*actor),
+            action,
+            slot: u64::from_le_bytes(*slot),
+            unix_timestamp: i64::from_le_bytes(*unix_timestamp),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8; AccessLogEntry::LEN]) {
+        let (actor_dst, action_dst, slot_dst, unix_timestamp_dst) = mut_array_refs![dst, 32, 1, 8, 8];
+
+        actor_dst.copy_from_slice(self.actor.as_ref());
+        action_dst[0] = self.action as u8;
+        *slot_dst = self.slot.to_le_bytes();
+        *unix_timestamp_dst = self.unix_timestamp.to_le_bytes();
+    }
+}
+
+/// Ring buffer of the latest opens and closes of a door, kept in a PDA
+/// created alongside the door
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct AccessLog {
+    /// The door being logged
+    pub door: Pubkey,
+    /// Default to false
+    pub is_initialized: bool,
+    /// Bump seed of the log address
+    pub bump: u8,
+    /// Number of entries ever appended
+    pub count: u64,
+    /// The ring buffer, one entry per slot; entry `count % ACCESS_LOG_CAPACITY`
+    /// is the next one overwritten
+    pub entries: Vec<AccessLogEntry>,
+}
+
+impl AccessLog {
+    /// Creates an empty log of `door`
+    pub fn new(door: Pubkey, bump: u8) -> Self {
+        AccessLog {
+            door,
+            is_initialized: true,
+            bump,
+            count: 0,
+            entries: vec![AccessLogEntry::default(); ACCESS_LOG_CAPACITY],
+        }
+    }
+
+    /// Derives the address of the access log of `door`
+    pub fn find_address(program_id: &Pubkey, door: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[ACCESS_LOG_SEED, door.as_ref()], program_id)
     }
-}
\ No newline at end of file
+
+    /// Appends `entry`, overwriting the oldest one once the log is full
+    pub fn push(&mut self, entry: AccessLogEntry) {
+        let slot = (self.count % ACCESS_LOG_CAPACITY as u64) as usize;
+        self.entries[slot] = entry;
+        self.count = self.count.wrapping_add(1);
+    }
+
+    /// Returns the entries still held, oldest first
+    pub fn entries_in_order(&self) -> impl Iterator<Item = &AccessLogEntry> {
+        let len = self.count.min(ACCESS_LOG_CAPACITY as u64) as usize;
+        let start = if len < ACCESS_LOG_CAPACITY {
+            0
+        } else {
+            (self.count % ACCESS_LOG_CAPACITY as u64) as usize
+        };
+        (0..len).map(move |i| &self.entries[(start + i) % ACCESS_LOG_CAPACITY])
+    }
+}
+
+const _: () = assert!(
+    AccessLog::LEN != Door::LEN && AccessLog::LEN != Config::LEN && AccessLog::LEN != Grant::LEN
+);
+
+impl Sealed for AccessLog {}
+impl IsInitialized for AccessLog {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for AccessLog {
+    const LEN: usize = 1610;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, AccessLog::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (door, is_initialized, bump, count, entries_flat) =
+            array_refs![src, 32, 1, 1, 8, AccessLogEntry::LEN * ACCESS_LOG_CAPACITY];
+
+        let is_initialized = match is_initialized {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        let entries = entries_flat
+            .chunks(AccessLogEntry::LEN)
+            .map(|src| AccessLogEntry::unpack_from_slice(array_ref![src, 0, AccessLogEntry::LEN]))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(AccessLog {
+            door: Pubkey::new_from_array(*door),
+            is_initialized,
+            bump: bump[0],
+            count: u64::from_le_bytes(*count),
+            entries,
+        })
+    }
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, AccessLog::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (door_dst, is_initialized_dst, bump_dst, count_dst, entries_flat) =
+            mut_array_refs![dst, 32, 1, 1, 8, AccessLogEntry::LEN * ACCESS_LOG_CAPACITY];
+
+        door_dst.copy_from_slice(self.door.as_ref());
+        is_initialized_dst[0] = self.is_initialized as u8;
+        bump_dst[0] = self.bump;
+        *count_dst = self.count.to_le_bytes();
+        for (i, entry) in self.entries.iter().take(ACCESS_LOG_CAPACITY).enumerate() {
+            let dst_array = array_mut_ref![entries_flat, AccessLogEntry::LEN * i, AccessLogEntry::LEN];
+            entry.pack_into_slice(dst_array);
+        }
+    }
+}
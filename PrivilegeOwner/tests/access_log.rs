@@ -0,0 +1,105 @@
+mod common;
+
+use common::*;
+use solana_program::{instruction::AccountMeta, program_pack::Pack, pubkey::Pubkey};
+use solana_sdk::{
+    instruction::InstructionError,
+    signature::{Keypair, Signer},
+};
+use PrivilegeOwner::state::{AccessAction, AccessLog, AccessLogEntry, ACCESS_LOG_CAPACITY};
+
+fn slots(log: &AccessLog) -> Vec<u64> {
+    log.entries_in_order().map(|entry| entry.slot).collect()
+}
+
+#[test]
+fn test_entries_in_order_wrap_around() {
+    let mut log = AccessLog::new(Pubkey::new_unique(), 255);
+    assert!(slots(&log).is_empty());
+
+    let capacity = ACCESS_LOG_CAPACITY as u64;
+    for slot in 0..capacity + 5 {
+        log.push(AccessLogEntry {
+            slot,
+            ..AccessLogEntry::default()
+        });
+        // the oldest entries are overwritten once the log is full
+        let first = (slot + 1).saturating_sub(capacity);
+        assert_eq!(slots(&log), (first..=slot).collect::<Vec<_>>());
+    }
+
+    let mut packed = vec![0; AccessLog::LEN];
+    AccessLog::pack(log, &mut packed).unwrap();
+    let log = AccessLog::unpack(&packed).unwrap();
+    assert_eq!(log.count, capacity + 5);
+    assert_eq!(slots(&log), (5..capacity + 5).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn test_log_opens_and_closes() {
+    let (mut context, program_id, config) = start().await;
+    let alice = Keypair::new();
+    let door = add_door(&mut context, program_id, config, alice.pubkey(), 0).await;
+    let log = load_log(&mut context, program_id, door).await;
+    assert_eq!(log.door, door);
+    assert_eq!(log.count, 0);
+
+    warp_to_timestamp(&mut context, 1_000).await;
+    let mut instructions = vec![];
+    for _ in 0..ACCESS_LOG_CAPACITY / 2 + 2 {
+        instructions.push(open(program_id, door, config, alice.pubkey(), None));
+        instructions.push(close(program_id, door, config, alice.pubkey(), None));
+    }
+    process(&mut context, &instructions, &[&alice]).await.unwrap();
+
+    let log = load_log(&mut context, program_id, door).await;
+    assert_eq!(log.count, ACCESS_LOG_CAPACITY as u64 + 4);
+    let entries: Vec<&AccessLogEntry> = log.entries_in_order().collect();
+    assert_eq!(entries.len(), ACCESS_LOG_CAPACITY);
+    assert_eq!(entries[0].action, AccessAction::Open);
+    assert_eq!(entries[ACCESS_LOG_CAPACITY - 1].action, AccessAction::Close);
+    assert!(entries
+        .iter()
+        .all(|entry| entry.actor == alice.pubkey() && entry.unix_timestamp == 1_000));
+}
+
+#[tokio::test]
+async fn test_log_emergency_lock_by_guardian() {
+    let (mut context, program_id, config) = start().await;
+    let payer = context.payer.pubkey();
+    let (alice, guardian) = (Keypair::new(), Keypair::new());
+    let open_door = add_door(&mut context, program_id, config, alice.pubkey(), 0).await;
+    let closed_door = add_door(&mut context, program_id, config, alice.pubkey(), 1).await;
+    let instructions = [
+        open(program_id, open_door, config, alice.pubkey(), None),
+        set_guardians(program_id, config, payer, &[guardian.pubkey()]),
+    ];
+    process(&mut context, &instructions, &[&alice]).await.unwrap();
+
+    let doors = [open_door, closed_door];
+    let instructions = [emergency_lock(program_id, config, guardian.pubkey(), &doors)];
+    process(&mut context, &instructions, &[&guardian]).await.unwrap();
+
+    let log = load_log(&mut context, program_id, open_door).await;
+    assert_eq!(log.count, 2);
+    let last = log.entries_in_order().last().unwrap();
+    assert_eq!(last.actor, guardian.pubkey());
+    assert_eq!(last.action, AccessAction::Close);
+    // a door that was not open has nothing to log
+    assert_eq!(load_log(&mut context, program_id, closed_door).await.count, 0);
+}
+
+#[tokio::test]
+async fn test_reject_log_of_other_door() {
+    let (mut context, program_id, config) = start().await;
+    let alice = Keypair::new();
+    let door = add_door(&mut context, program_id, config, alice.pubkey(), 0).await;
+    let other = add_door(&mut context, program_id, config, alice.pubkey(), 1).await;
+
+    let mut instruction = open(program_id, door, config, alice.pubkey(), None);
+    let (other_log, _) = AccessLog::find_address(&program_id, &other);
+    instruction.accounts[1] = AccountMeta::new(other_log, false);
+    let result = process(&mut context, &[instruction], &[&alice]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    assert_eq!(load_log(&mut context, program_id, other).await.count, 0);
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use common::*;
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use solana_sdk::{
+    instruction::InstructionError,
+    signature::{Keypair, Signer},
+};
+
+fn propose_admin(program_id: Pubkey, config: Pubkey, admin: Pubkey, key: Pubkey) -> Instruction {
+    let mut data = vec![8];
+    data.extend_from_slice(key.as_ref());
+    config_instruction(program_id, config, admin, data)
+}
+
+fn accept_admin(program_id: Pubkey, config: Pubkey, pending_admin: Pubkey) -> Instruction {
+    config_instruction(program_id, config, pending_admin, vec![9])
+}
+
+fn cancel_admin_transfer(program_id: Pubkey, config: Pubkey, admin: Pubkey) -> Instruction {
+    config_instruction(program_id, config, admin, vec![10])
+}
+
+#[tokio::test]
+async fn test_transfer_admin() {
+    let (mut context, program_id, config) = start().await;
+    let payer = context.payer.pubkey();
+    let bob = Keypair::new();
+
+    let instructions = [propose_admin(program_id, config, payer, bob.pubkey())];
+    process(&mut context, &instructions, &[]).await.unwrap();
+    let proposed = load_config(&mut context, config).await;
+    assert_eq!(proposed.admin, payer);
+    assert_eq!(proposed.pending_admin, Some(bob.pubkey()));
+
+    let instructions = [accept_admin(program_id, config, bob.pubkey())];
+    process(&mut context, &instructions, &[&bob]).await.unwrap();
+    let accepted = load_config(&mut context, config).await;
+    assert_eq!(accepted.admin, bob.pubkey());
+    assert_eq!(accepted.pending_admin, None);
+
+    // only the new admin can lock
+    let lock = |admin: Pubkey| config_instruction(program_id, config, admin, vec![2]);
+    let result = process(&mut context, &[lock(payer)], &[]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    process(&mut context, &[lock(bob.pubkey())], &[&bob]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_reject_proposal_by_non_admin() {
+    let (mut context, program_id, config) = start().await;
+    let bob = Keypair::new();
+
+    let instructions = [propose_admin(program_id, config, bob.pubkey(), bob.pubkey())];
+    let result = process(&mut context, &instructions, &[&bob]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    assert_eq!(load_config(&mut context, config).await.pending_admin, None);
+}
+
+#[tokio::test]
+async fn test_reject_accept_by_other_key() {
+    let (mut context, program_id, config) = start().await;
+    let payer = context.payer.pubkey();
+    let bob = Keypair::new();
+
+    // nothing to accept yet
+    let instructions = [accept_admin(program_id, config, bob.pubkey())];
+    let result = process(&mut context, &instructions, &[&bob]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+
+    let instructions = [propose_admin(program_id, config, payer, bob.pubkey())];
+    process(&mut context, &instructions, &[]).await.unwrap();
+    // the current admin cannot accept on behalf of the pending one
+    let instructions = [accept_admin(program_id, config, payer)];
+    let result = process(&mut context, &instructions, &[]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    assert_eq!(load_config(&mut context, config).await.admin, payer);
+}
+
+#[tokio::test]
+async fn test_cancel_admin_transfer() {
+    let (mut context, program_id, config) = start().await;
+    let payer = context.payer.pubkey();
+    let bob = Keypair::new();
+
+    let instructions = [
+        propose_admin(program_id, config, payer, bob.pubkey()),
+        cancel_admin_transfer(program_id, config, payer),
+    ];
+    process(&mut context, &instructions, &[]).await.unwrap();
+    assert_eq!(load_config(&mut context, config).await.pending_admin, None);
+
+    let instructions = [accept_admin(program_id, config, bob.pubkey())];
+    let result = process(&mut context, &instructions, &[&bob]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    assert_eq!(load_config(&mut context, config).await.admin, payer);
+
+    // nothing left to cancel
+    let instructions = [cancel_admin_transfer(program_id, config, payer)];
+    let result = process(&mut context, &instructions, &[]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+}
@@ -0,0 +1,308 @@
+//! Helpers shared by the program tests
+#![allow(dead_code)]
+
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_instruction, system_program,
+    sysvar::clock::Clock,
+};
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    instruction::InstructionError,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+    transport::TransportError,
+};
+use PrivilegeOwner::{
+    processor::process_instruction,
+    state::{AccessLog, Config, Door, Grant},
+};
+
+/// Seed of the config PDA
+pub const CONFIG_SEED: &[u8] = b"You pass butter";
+
+/// Starts a bank with the program loaded at a fresh id and an unlocked
+/// config administered by the payer
+pub async fn start() -> (ProgramTestContext, Pubkey, Pubkey) {
+    let program_id = Pubkey::new_unique();
+    let mut context = ProgramTest::new(
+        "PrivilegeOwner",
+        program_id,
+        processor!(process_instruction),
+    )
+    .start_with_context()
+    .await;
+    let payer = context.payer.pubkey();
+    let (config, _) = Pubkey::find_program_address(&[CONFIG_SEED], &program_id);
+
+    let mut initialize = vec![1];
+    initialize.extend_from_slice(payer.as_ref());
+    let instructions = [
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new(config, false),
+                AccountMeta::new(payer, true),
+            ],
+            data: vec![6],
+        },
+        Instruction {
+            program_id,
+            accounts: vec![AccountMeta::new(config, false)],
+            data: initialize,
+        },
+        config_instruction(program_id, config, payer, vec![3]),
+    ];
+    process(&mut context, &instructions, &[]).await.unwrap();
+
+    (context, program_id, config)
+}
+
+/// Sends `instructions` in one transaction paid for by the payer
+pub async fn process(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransportError> {
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.into())
+}
+
+/// Sends lamports from the payer to `key`, so it can pay for accounts
+pub async fn fund(context: &mut ProgramTestContext, key: Pubkey) {
+    let payer = context.payer.pubkey();
+    let instructions = [system_instruction::transfer(&payer, &key, 1_000_000_000)];
+    process(context, &instructions, &[]).await.unwrap();
+}
+
+/// An instruction on the config alone, signed by `signer`
+pub fn config_instruction(
+    program_id: Pubkey,
+    config: Pubkey,
+    signer: Pubkey,
+    data: Vec<u8>,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(signer, true),
+        ],
+        data,
+    }
+}
+
+/// The CreateDoor instruction of the door of `key` with `index`, and the door
+pub fn create_door(
+    program_id: Pubkey,
+    config: Pubkey,
+    admin: Pubkey,
+    key: Pubkey,
+    index: u64,
+) -> (Instruction, Pubkey) {
+    let (door, _) = Door::find_address(&program_id, &config, &key, index);
+    let (log, _) = AccessLog::find_address(&program_id, &door);
+    let mut data = vec![7];
+    data.extend_from_slice(key.as_ref());
+    data.extend_from_slice(&index.to_le_bytes());
+    let instruction = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(door, false),
+            AccountMeta::new(log, false),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new(admin, true),
+        ],
+        data,
+    };
+    (instruction, door)
+}
+
+/// Creates the door of `key` with `index`, signed by the payer as admin
+pub async fn add_door(
+    context: &mut ProgramTestContext,
+    program_id: Pubkey,
+    config: Pubkey,
+    key: Pubkey,
+    index: u64,
+) -> Pubkey {
+    let payer = context.payer.pubkey();
+    let (instruction, door) = create_door(program_id, config, payer, key, index);
+    process(context, &[instruction], &[]).await.unwrap();
+    door
+}
+
+fn door_instruction(
+    program_id: Pubkey,
+    door: Pubkey,
+    config: Pubkey,
+    owner: Pubkey,
+    grant: Option<Pubkey>,
+    tag: u8,
+) -> Instruction {
+    let (log, _) = AccessLog::find_address(&program_id, &door);
+    let mut accounts = vec![
+        AccountMeta::new(door, false),
+        AccountMeta::new(log, false),
+        AccountMeta::new_readonly(config, false),
+        AccountMeta::new_readonly(owner, true),
+    ];
+    if let Some(grant) = grant {
+        accounts.push(AccountMeta::new(grant, false));
+    }
+    Instruction {
+        program_id,
+        accounts,
+        data: vec![tag],
+    }
+}
+
+/// The Open instruction of `door` by its key holder, or by a grantee with `grant`
+pub fn open(
+    program_id: Pubkey,
+    door: Pubkey,
+    config: Pubkey,
+    owner: Pubkey,
+    grant: Option<Pubkey>,
+) -> Instruction {
+    door_instruction(program_id, door, config, owner, grant, 4)
+}
+
+/// The Close instruction of `door` by its key holder, or by a grantee with `grant`
+pub fn close(
+    program_id: Pubkey,
+    door: Pubkey,
+    config: Pubkey,
+    owner: Pubkey,
+    grant: Option<Pubkey>,
+) -> Instruction {
+    door_instruction(program_id, door, config, owner, grant, 5)
+}
+
+/// The IssueGrant instruction of `door` to `grantee`, and the grant
+pub fn issue_grant(
+    program_id: Pubkey,
+    door: Pubkey,
+    key: Pubkey,
+    grantee: Pubkey,
+    valid_from: i64,
+    valid_until: i64,
+    uses: Option<u32>,
+) -> (Instruction, Pubkey) {
+    let (grant, _) = Grant::find_address(&program_id, &door, &grantee);
+    let mut data = vec![11];
+    data.extend_from_slice(grantee.as_ref());
+    data.extend_from_slice(&valid_from.to_le_bytes());
+    data.extend_from_slice(&valid_until.to_le_bytes());
+    data.push(uses.is_some() as u8);
+    data.extend_from_slice(&uses.unwrap_or_default().to_le_bytes());
+    let instruction = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(grant, false),
+            AccountMeta::new_readonly(door, false),
+            AccountMeta::new(key, true),
+        ],
+        data,
+    };
+    (instruction, grant)
+}
+
+/// The SetGuardians instruction, signed by `admin`
+pub fn set_guardians(
+    program_id: Pubkey,
+    config: Pubkey,
+    admin: Pubkey,
+    guardians: &[Pubkey],
+) -> Instruction {
+    let mut data = vec![13, guardians.len() as u8];
+    for guardian in guardians {
+        data.extend_from_slice(guardian.as_ref());
+    }
+    config_instruction(program_id, config, admin, data)
+}
+
+/// The EmergencyLock instruction, signed by `guardian`, passing `doors`
+/// with their logs
+pub fn emergency_lock(
+    program_id: Pubkey,
+    config: Pubkey,
+    guardian: Pubkey,
+    doors: &[Pubkey],
+) -> Instruction {
+    let mut instruction = config_instruction(program_id, config, guardian, vec![14]);
+    for door in doors {
+        let (log, _) = AccessLog::find_address(&program_id, door);
+        instruction.accounts.push(AccountMeta::new(*door, false));
+        instruction.accounts.push(AccountMeta::new(log, false));
+    }
+    instruction
+}
+
+pub async fn load_door(context: &mut ProgramTestContext, door: Pubkey) -> Door {
+    let account = context.banks_client.get_account(door).await.unwrap().unwrap();
+    Door::unpack(&account.data).unwrap()
+}
+
+pub async fn load_config(context: &mut ProgramTestContext, config: Pubkey) -> Config {
+    let account = context.banks_client.get_account(config).await.unwrap().unwrap();
+    Config::unpack(&account.data).unwrap()
+}
+
+pub async fn load_grant(context: &mut ProgramTestContext, grant: Pubkey) -> Grant {
+    let account = context.banks_client.get_account(grant).await.unwrap().unwrap();
+    Grant::unpack(&account.data).unwrap()
+}
+
+pub async fn load_log(
+    context: &mut ProgramTestContext,
+    program_id: Pubkey,
+    door: Pubkey,
+) -> AccessLog {
+    let (log, _) = AccessLog::find_address(&program_id, &door);
+    let account = context.banks_client.get_account(log).await.unwrap().unwrap();
+    AccessLog::unpack(&account.data).unwrap()
+}
+
+/// Waits for a new blockhash, so resending the same instructions makes a
+/// new transaction rather than a duplicate
+pub async fn refresh_blockhash(context: &mut ProgramTestContext) {
+    // get_new_latest_blockhash waits for a hash other than last_blockhash,
+    // which `process` does not keep up to date
+    context.last_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    context.get_new_latest_blockhash().await.unwrap();
+}
+
+/// Moves the bank forward and sets the clock to `unix_timestamp`
+pub async fn warp_to_timestamp(context: &mut ProgramTestContext, unix_timestamp: i64) {
+    let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    context.warp_to_slot(clock.slot + 100).unwrap();
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = unix_timestamp;
+    context.set_sysvar(&clock);
+}
+
+pub fn assert_instruction_error(result: Result<(), TransportError>, expected: InstructionError) {
+    match result.unwrap_err() {
+        TransportError::TransactionError(TransactionError::InstructionError(_, err))
+            if err == expected => {}
+        err => panic!("unexpected error {:?}", err),
+    }
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use common::*;
+use solana_sdk::{
+    instruction::InstructionError,
+    signature::{Keypair, Signer},
+};
+
+#[tokio::test]
+async fn test_create_doors_of_one_key() {
+    let (mut context, program_id, config) = start().await;
+    let alice = Keypair::new();
+    let first = add_door(&mut context, program_id, config, alice.pubkey(), 0).await;
+    let second = add_door(&mut context, program_id, config, alice.pubkey(), 1).await;
+    assert_ne!(first, second);
+
+    let door = load_door(&mut context, first).await;
+    assert_eq!(door.key, alice.pubkey());
+    assert_eq!(door.config, config);
+    assert_eq!(door.index, 0);
+    assert!(!door.is_opened);
+
+    // the doors open and close independently
+    let instructions = [open(program_id, first, config, alice.pubkey(), None)];
+    process(&mut context, &instructions, &[&alice]).await.unwrap();
+    assert!(load_door(&mut context, first).await.is_opened);
+    assert!(!load_door(&mut context, second).await.is_opened);
+    let instructions = [close(program_id, first, config, alice.pubkey(), None)];
+    process(&mut context, &instructions, &[&alice]).await.unwrap();
+    assert!(!load_door(&mut context, first).await.is_opened);
+}
+
+#[tokio::test]
+async fn test_reject_door_created_by_non_admin() {
+    let (mut context, program_id, config) = start().await;
+    let mallory = Keypair::new();
+    fund(&mut context, mallory.pubkey()).await;
+
+    let (instruction, door) = create_door(program_id, config, mallory.pubkey(), mallory.pubkey(), 0);
+    let result = process(&mut context, &[instruction], &[&mallory]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    assert!(context.banks_client.get_account(door).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_reject_open_by_other_key() {
+    let (mut context, program_id, config) = start().await;
+    let payer = context.payer.pubkey();
+    let alice = Keypair::new();
+    let door = add_door(&mut context, program_id, config, alice.pubkey(), 0).await;
+
+    // the admin holds no key to the door and has no grant for it
+    let result = process(&mut context, &[open(program_id, door, config, payer, None)], &[]).await;
+    assert_instruction_error(result, InstructionError::MissingRequiredSignature);
+    assert!(!load_door(&mut context, door).await.is_opened);
+}
+
+#[tokio::test]
+async fn test_reject_config_passed_as_door() {
+    let (mut context, program_id, config) = start().await;
+    let alice = Keypair::new();
+    add_door(&mut context, program_id, config, alice.pubkey(), 0).await;
+
+    let instructions = [open(program_id, config, config, alice.pubkey(), None)];
+    let result = process(&mut context, &instructions, &[&alice]).await;
+    assert_instruction_error(result, InstructionError::InvalidAccountData);
+}
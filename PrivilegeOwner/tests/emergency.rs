@@ -0,0 +1,122 @@
+mod common;
+
+use common::*;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    instruction::InstructionError,
+    signature::{Keypair, Signer},
+};
+use PrivilegeOwner::state::MAX_GUARDIANS;
+
+/// Opens two doors of `alice` and makes `guardian` the only guardian
+async fn setup(alice: &Keypair, guardian: &Keypair) -> (ProgramTestContext, Pubkey, Pubkey, [Pubkey; 2]) {
+    let (mut context, program_id, config) = start().await;
+    let payer = context.payer.pubkey();
+    let doors = [
+        add_door(&mut context, program_id, config, alice.pubkey(), 0).await,
+        add_door(&mut context, program_id, config, alice.pubkey(), 1).await,
+    ];
+    let instructions = [
+        open(program_id, doors[0], config, alice.pubkey(), None),
+        open(program_id, doors[1], config, alice.pubkey(), None),
+        set_guardians(program_id, config, payer, &[guardian.pubkey()]),
+    ];
+    process(&mut context, &instructions, &[alice]).await.unwrap();
+
+    (context, program_id, config, doors)
+}
+
+#[tokio::test]
+async fn test_emergency_lock_closes_every_door() {
+    let (alice, guardian) = (Keypair::new(), Keypair::new());
+    let (mut context, program_id, config, doors) = setup(&alice, &guardian).await;
+
+    // only the first door is passed
+    let instructions = [emergency_lock(program_id, config, guardian.pubkey(), &doors[0..1])];
+    process(&mut context, &instructions, &[&guardian]).await.unwrap();
+    let locked = load_config(&mut context, config).await;
+    assert!(locked.is_locked && locked.is_emergency);
+    for door in doors {
+        assert!(!load_door(&mut context, door).await.is_open(&locked));
+    }
+
+    // once unlocked, the second door is closed too and has to be opened again
+    let unlock = config_instruction(program_id, config, context.payer.pubkey(), vec![3]);
+    process(&mut context, &[unlock], &[]).await.unwrap();
+    let instructions = [close(program_id, doors[1], config, alice.pubkey(), None)];
+    let result = process(&mut context, &instructions, &[&alice]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    let instructions = [
+        open(program_id, doors[1], config, alice.pubkey(), None),
+        close(program_id, doors[1], config, alice.pubkey(), None),
+    ];
+    process(&mut context, &instructions, &[&alice]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_reject_emergency_lock_by_non_guardian() {
+    let (alice, guardian) = (Keypair::new(), Keypair::new());
+    let (mut context, program_id, config, doors) = setup(&alice, &guardian).await;
+    let payer = context.payer.pubkey();
+
+    // not even the admin is a guardian
+    let instructions = [emergency_lock(program_id, config, payer, &doors)];
+    let result = process(&mut context, &instructions, &[]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    let instructions = [emergency_lock(program_id, config, alice.pubkey(), &doors)];
+    let result = process(&mut context, &instructions, &[&alice]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+
+    let config = load_config(&mut context, config).await;
+    assert!(!config.is_locked && !config.is_emergency);
+    assert!(load_door(&mut context, doors[0]).await.is_open(&config));
+}
+
+#[tokio::test]
+async fn test_only_admin_unlocks_after_emergency() {
+    let (alice, guardian) = (Keypair::new(), Keypair::new());
+    let (mut context, program_id, config, doors) = setup(&alice, &guardian).await;
+    let payer = context.payer.pubkey();
+    let instructions = [emergency_lock(program_id, config, guardian.pubkey(), &doors)];
+    process(&mut context, &instructions, &[&guardian]).await.unwrap();
+
+    let unlock = |signer: Pubkey| config_instruction(program_id, config, signer, vec![3]);
+    let result = process(&mut context, &[unlock(guardian.pubkey())], &[&guardian]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    let instructions = [open(program_id, doors[0], config, alice.pubkey(), None)];
+    let result = process(&mut context, &instructions, &[&alice]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+
+    process(&mut context, &[unlock(payer)], &[]).await.unwrap();
+    let unlocked = load_config(&mut context, config).await;
+    assert!(!unlocked.is_locked && !unlocked.is_emergency);
+    process(&mut context, &instructions, &[&alice]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_only_admin_sets_valid_guardians() {
+    let (mut context, program_id, config) = start().await;
+    let payer = context.payer.pubkey();
+    let guardian = Keypair::new();
+
+    let instructions = [set_guardians(program_id, config, guardian.pubkey(), &[guardian.pubkey()])];
+    let result = process(&mut context, &instructions, &[&guardian]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+
+    let too_many: Vec<Pubkey> = (0..=MAX_GUARDIANS).map(|_| Pubkey::new_unique()).collect();
+    let duplicate = [guardian.pubkey(), guardian.pubkey()];
+    let zero = [guardian.pubkey(), Pubkey::default()];
+    for guardians in [&too_many[..], &duplicate, &zero] {
+        let instructions = [set_guardians(program_id, config, payer, guardians)];
+        let result = process(&mut context, &instructions, &[]).await;
+        assert_instruction_error(result, InstructionError::InvalidArgument);
+    }
+    assert_eq!(load_config(&mut context, config).await.guardian_count, 0);
+
+    let instructions = [set_guardians(program_id, config, payer, &[guardian.pubkey()])];
+    process(&mut context, &instructions, &[]).await.unwrap();
+    let config = load_config(&mut context, config).await;
+    assert!(config.is_guardian(&guardian.pubkey()));
+    assert!(!config.is_guardian(&payer));
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use common::*;
+use std::ops::Range;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    instruction::InstructionError,
+    signature::{Keypair, Signer},
+};
+
+const NOW: i64 = 1_000;
+
+/// Creates a door of `alice`, funded to pay for grants, with the clock at `NOW`
+async fn setup(alice: &Keypair) -> (ProgramTestContext, Pubkey, Pubkey, Pubkey) {
+    let (mut context, program_id, config) = start().await;
+    fund(&mut context, alice.pubkey()).await;
+    let door = add_door(&mut context, program_id, config, alice.pubkey(), 0).await;
+    warp_to_timestamp(&mut context, NOW).await;
+
+    (context, program_id, config, door)
+}
+
+/// Issues a grant of `door` from `alice` to `grantee`
+async fn grant(
+    context: &mut ProgramTestContext,
+    program_id: Pubkey,
+    door: Pubkey,
+    alice: &Keypair,
+    grantee: Pubkey,
+    window: Range<i64>,
+    uses: Option<u32>,
+) -> Pubkey {
+    let (instruction, grant) = issue_grant(
+        program_id,
+        door,
+        alice.pubkey(),
+        grantee,
+        window.start,
+        window.end,
+        uses,
+    );
+    process(context, &[instruction], &[alice]).await.unwrap();
+    grant
+}
+
+#[tokio::test]
+async fn test_grant_only_valid_in_its_window() {
+    let (alice, bob) = (Keypair::new(), Keypair::new());
+    let (mut context, program_id, config, door) = setup(&alice).await;
+    let grant = grant(&mut context, program_id, door, &alice, bob.pubkey(), NOW + 10..NOW + 100, None).await;
+    let open = [open(program_id, door, config, bob.pubkey(), Some(grant))];
+    let close = [close(program_id, door, config, bob.pubkey(), Some(grant))];
+
+    let result = process(&mut context, &open, &[&bob]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+
+    warp_to_timestamp(&mut context, NOW + 10).await;
+    process(&mut context, &open, &[&bob]).await.unwrap();
+    assert!(load_door(&mut context, door).await.is_opened);
+
+    // valid_until is exclusive
+    warp_to_timestamp(&mut context, NOW + 100).await;
+    let result = process(&mut context, &close, &[&bob]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    assert!(load_door(&mut context, door).await.is_opened);
+}
+
+#[tokio::test]
+async fn test_grant_uses_counted_on_open() {
+    let (alice, bob) = (Keypair::new(), Keypair::new());
+    let (mut context, program_id, config, door) = setup(&alice).await;
+    let grant = grant(&mut context, program_id, door, &alice, bob.pubkey(), NOW..NOW + 100, Some(1)).await;
+    let open = open(program_id, door, config, bob.pubkey(), Some(grant));
+    let close = close(program_id, door, config, bob.pubkey(), Some(grant));
+
+    // closing is free, only opening uses the grant up
+    process(&mut context, &[open.clone(), close], &[&bob]).await.unwrap();
+    assert_eq!(load_grant(&mut context, grant).await.remaining_uses, Some(0));
+
+    let result = process(&mut context, &[open], &[&bob]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    assert!(!load_door(&mut context, door).await.is_opened);
+}
+
+#[tokio::test]
+async fn test_reissue_grant_with_new_terms() {
+    let (alice, bob) = (Keypair::new(), Keypair::new());
+    let (mut context, program_id, config, door) = setup(&alice).await;
+    let first = grant(&mut context, program_id, door, &alice, bob.pubkey(), NOW..NOW + 100, Some(1)).await;
+    let second = grant(&mut context, program_id, door, &alice, bob.pubkey(), NOW..NOW + 200, None).await;
+    assert_eq!(first, second);
+
+    let reissued = load_grant(&mut context, second).await;
+    assert_eq!(reissued.valid_until, NOW + 200);
+    assert_eq!(reissued.remaining_uses, None);
+    let instructions = [
+        open(program_id, door, config, bob.pubkey(), Some(second)),
+        close(program_id, door, config, bob.pubkey(), Some(second)),
+        open(program_id, door, config, bob.pubkey(), Some(second)),
+    ];
+    process(&mut context, &instructions, &[&bob]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_reject_grant_by_other_key() {
+    let (alice, bob) = (Keypair::new(), Keypair::new());
+    let (mut context, program_id, _config, door) = setup(&alice).await;
+    let payer = context.payer.pubkey();
+
+    let (instruction, grant) = issue_grant(program_id, door, payer, bob.pubkey(), NOW, NOW + 100, None);
+    let result = process(&mut context, &[instruction], &[]).await;
+    assert_instruction_error(result, InstructionError::InvalidArgument);
+    assert!(context.banks_client.get_account(grant).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_reject_grant_that_cannot_open() {
+    let (alice, bob) = (Keypair::new(), Keypair::new());
+    let (mut context, program_id, _config, door) = setup(&alice).await;
+
+    let empty_window = issue_grant(program_id, door, alice.pubkey(), bob.pubkey(), NOW, NOW, None);
+    let no_uses = issue_grant(program_id, door, alice.pubkey(), bob.pubkey(), NOW, NOW + 100, Some(0));
+    for (instruction, grant) in [empty_window, no_uses] {
+        let result = process(&mut context, &[instruction], &[&alice]).await;
+        assert_instruction_error(result, InstructionError::InvalidArgument);
+        assert!(context.banks_client.get_account(grant).await.unwrap().is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_revoke_grant() {
+    let (alice, bob) = (Keypair::new(), Keypair::new());
+    let (mut context, program_id, config, door) = setup(&alice).await;
+    let grant = grant(&mut context, program_id, door, &alice, bob.pubkey(), NOW..NOW + 100, None).await;
+
+    let revoke = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(grant, false),
+            AccountMeta::new_readonly(door, false),
+            AccountMeta::new(alice.pubkey(), true),
+        ],
+        data: vec![12],
+    };
+    process(&mut context, &[revoke], &[&alice]).await.unwrap();
+    assert!(context.banks_client.get_account(grant).await.unwrap().is_none());
+
+    let instructions = [open(program_id, door, config, bob.pubkey(), Some(grant))];
+    let result = process(&mut context, &instructions, &[&bob]).await;
+    assert_instruction_error(result, InstructionError::IncorrectProgramId);
+}